@@ -0,0 +1,438 @@
+//! Arbitrary-precision unsigned integers for generating huge numbers.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::math::{DIGITS_62, convolution_exact};
+
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+// Below this many limbs in the shorter factor, schoolbook multiplication
+// beats the three-prime NTT.
+const NTT_THRESHOLD: usize = 512;
+
+/// Unsigned integer of any size, stored as base-10^9 limbs, least
+/// significant first. Zero has no limbs and the top limb is never zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+/// Error returned when parsing a `BigUint` from a string that is not a
+/// non-empty run of decimal digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid digit string for BigUint")
+    }
+}
+
+impl std::error::Error for ParseBigUintError {}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self { limbs: vec![1] }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn trim(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    /// Number of decimal digits (1 for zero).
+    pub fn digit_count(&self) -> usize {
+        match self.limbs.last() {
+            None => 1,
+            Some(&top) => (self.limbs.len() - 1) * BASE_DIGITS + top.ilog10() as usize + 1,
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_u128().and_then(|v| u64::try_from(v).ok())
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        let mut res = 0u128;
+        for &limb in self.limbs.iter().rev() {
+            res = res.checked_mul(BASE as u128)?.checked_add(limb as u128)?;
+        }
+        Some(res)
+    }
+
+    /// `self * m` for a machine-sized factor.
+    pub fn mul_small(&self, m: u64) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 3);
+        let mut carry = 0u128;
+        for &limb in &self.limbs {
+            let cur = limb as u128 * m as u128 + carry;
+            limbs.push((cur % BASE as u128) as u32);
+            carry = cur / BASE as u128;
+        }
+        while carry > 0 {
+            limbs.push((carry % BASE as u128) as u32);
+            carry /= BASE as u128;
+        }
+        BigUint { limbs }.trim()
+    }
+
+    /// Quotient and remainder of division by a non-zero machine-sized
+    /// divisor.
+    pub fn div_rem_small(&self, d: u64) -> (BigUint, u64) {
+        assert!(d != 0, "division by zero");
+        let mut limbs = vec![0u32; self.limbs.len()];
+        let mut rem = 0u128;
+        for i in (0..self.limbs.len()).rev() {
+            let cur = rem * BASE as u128 + self.limbs[i] as u128;
+            limbs[i] = (cur / d as u128) as u32;
+            rem = cur % d as u128;
+        }
+        (BigUint { limbs }.trim(), rem as u64)
+    }
+
+    /// `self * other` through `math::convolution_exact`, in
+    /// O(n log n) limb operations.
+    pub fn mul_ntt(&self, other: &BigUint) -> BigUint {
+        let a: Vec<u64> = self.limbs.iter().map(|&x| x as u64).collect();
+        let b: Vec<u64> = other.limbs.iter().map(|&x| x as u64).collect();
+        let mut limbs = Vec::with_capacity(a.len() + b.len() + 1);
+        let mut carry = 0u128;
+        for coefficient in convolution_exact(&a, &b) {
+            let cur = coefficient + carry;
+            limbs.push((cur % BASE as u128) as u32);
+            carry = cur / BASE as u128;
+        }
+        while carry > 0 {
+            limbs.push((carry % BASE as u128) as u32);
+            carry /= BASE as u128;
+        }
+        BigUint { limbs }.trim()
+    }
+
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0i64;
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let mut cur = limb as i64 - borrow - other.limbs.get(i).copied().unwrap_or(0) as i64;
+            borrow = if cur < 0 { 1 } else { 0 };
+            if cur < 0 {
+                cur += BASE as i64;
+            }
+            limbs.push(cur as u32);
+        }
+        Some(BigUint { limbs }.trim())
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigUint {
+        let mut base = self.clone();
+        let mut res = BigUint::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = &res * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        res
+    }
+
+    /// Digits in `radix` (2..=62), taken from `math::DIGITS_62`: upper-case
+    /// letters above 9, then lower-case ones above 35.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        assert!((2..=62).contains(&radix), "radix must be between 2 and 62");
+        const DIGITS: &[u8] = DIGITS_62.as_bytes();
+        if radix == 10 {
+            return self.to_string();
+        }
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        // Peel off as many digits at once as fit in one u32 chunk.
+        let mut chunk = radix as u64;
+        let mut per_chunk = 1;
+        while chunk * radix as u64 <= u32::MAX as u64 {
+            chunk *= radix as u64;
+            per_chunk += 1;
+        }
+        let mut buf = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, mut r) = n.div_rem_small(chunk);
+            for _ in 0..per_chunk {
+                buf.push(DIGITS[(r % radix as u64) as usize]);
+                r /= radix as u64;
+                if q.is_zero() && r == 0 {
+                    break;
+                }
+            }
+            n = q;
+        }
+        while buf.len() > 1 && buf.last() == Some(&b'0') {
+            buf.pop();
+        }
+        buf.reverse();
+        String::from_utf8(buf).unwrap_or_default()
+    }
+
+    /// Random number with exactly `digits` decimal digits and no leading
+    /// zero (a single digit may be 0).
+    pub fn random(rng: &mut impl Rng, digits: usize) -> BigUint {
+        assert!(digits > 0, "digits must be above zero");
+        let mut s = String::with_capacity(digits);
+        s.push(char::from(if digits == 1 {
+            b'0' + rng.random_range(0..10u8)
+        } else {
+            b'1' + rng.random_range(0..9u8)
+        }));
+        for _ in 1..digits {
+            s.push(char::from(b'0' + rng.random_range(0..10u8)));
+        }
+        s.parse().unwrap()
+    }
+
+    /// Uniformly random number in `[0, bound)`.
+    pub fn random_below(rng: &mut impl Rng, bound: &BigUint) -> BigUint {
+        assert!(!bound.is_zero(), "bound must be above zero");
+        let top = *bound.limbs.last().unwrap();
+        loop {
+            let mut limbs: Vec<u32> = (1..bound.limbs.len())
+                .map(|_| rng.random_range(0..BASE as u32))
+                .collect();
+            limbs.push(rng.random_range(0..=top));
+            let candidate = BigUint { limbs }.trim();
+            if candidate < *bound {
+                return candidate;
+            }
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(mut value: u64) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut value: u128) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % BASE as u128) as u32);
+            value /= BASE as u128;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigUintError);
+        }
+        let bytes = s.as_bytes();
+        let mut limbs = Vec::with_capacity(bytes.len() / BASE_DIGITS + 1);
+        let mut end = bytes.len();
+        while end > 0 {
+            let start = end.saturating_sub(BASE_DIGITS);
+            let limb = bytes[start..end].iter().fold(0u32, |acc, &b| acc * 10 + (b - b'0') as u32);
+            limbs.push(limb);
+            end = start;
+        }
+        Ok(BigUint { limbs }.trim())
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = match self.limbs.last() {
+            None => "0".to_string(),
+            Some(top) => top.to_string(),
+        };
+        for limb in self.limbs.iter().rev().skip(1) {
+            s.push_str(&format!("{:09}", limb));
+        }
+        f.pad(&s)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0u32;
+        for i in 0..len {
+            let cur = self.limbs.get(i).copied().unwrap_or(0)
+                + other.limbs.get(i).copied().unwrap_or(0)
+                + carry;
+            carry = (cur >= BASE as u32) as u32;
+            limbs.push(if carry == 1 { cur - BASE as u32 } else { cur });
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+        BigUint { limbs }
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    /// Panics when `other > self`.
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other).expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        if self.limbs.len().min(other.limbs.len()) >= NTT_THRESHOLD {
+            return self.mul_ntt(other);
+        }
+        let mut acc = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let cur = acc[i + j] + a as u64 * b as u64 + carry;
+                acc[i + j] = cur % BASE;
+                carry = cur / BASE;
+            }
+            acc[i + other.limbs.len()] += carry;
+        }
+        BigUint {
+            limbs: acc.into_iter().map(|limb| limb as u32).collect(),
+        }
+        .trim()
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: BigUint) -> BigUint {
+        &self + &other
+    }
+}
+
+impl Sub for BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: BigUint) -> BigUint {
+        &self - &other
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let n: BigUint = "000123456789012345678901234567890".parse().unwrap();
+        assert_eq!(n.to_string(), "123456789012345678901234567890");
+        assert_eq!(n.digit_count(), 30);
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!("".parse::<BigUint>(), Err(ParseBigUintError));
+        assert_eq!("12a".parse::<BigUint>(), Err(ParseBigUintError));
+        assert_eq!(BigUint::from(u128::MAX).to_u128(), Some(u128::MAX));
+        assert_eq!(BigUint::from(1u64 << 40).to_u64(), Some(1 << 40));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = BigUint::from(u64::MAX);
+        let b = BigUint::from(999_999_999u64);
+        let expected = u64::MAX as u128 * 999_999_999;
+        assert_eq!((&a * &b).to_u128(), Some(expected));
+        assert_eq!((&a + &b).to_u128(), Some(u64::MAX as u128 + 999_999_999));
+        assert_eq!((&a - &b).to_u64(), Some(u64::MAX - 999_999_999));
+        assert_eq!(b.checked_sub(&a), None);
+        assert_eq!(BigUint::from(2u64).pow(100).to_string(), "1267650600228229401496703205376");
+        let (q, r) = BigUint::from(2u64).pow(100).div_rem_small(1_000_000_007);
+        assert_eq!(q.mul_small(1_000_000_007) + BigUint::from(r), BigUint::from(2u64).pow(100));
+        assert!(a > b);
+    }
+
+    #[test]
+    fn test_radix_and_random() {
+        assert_eq!(BigUint::from(255u64).to_str_radix(16), "FF");
+        assert_eq!(BigUint::from(u128::MAX).to_str_radix(2), "1".repeat(128));
+        assert_eq!(BigUint::zero().to_str_radix(7), "0");
+        assert_eq!(BigUint::from(61u64).to_str_radix(62), "z");
+        assert_eq!(BigUint::from(62u64 * 62 * 62 - 1).to_str_radix(62), "zzz");
+
+        let mut rng = rand::rng();
+        let n = BigUint::random(&mut rng, 1000);
+        assert_eq!(n.digit_count(), 1000);
+        assert!(!n.to_string().starts_with('0'));
+        let bound = BigUint::from(10u64).pow(30);
+        assert!(BigUint::random_below(&mut rng, &bound) < bound);
+    }
+
+    #[test]
+    fn test_mul_ntt_matches_schoolbook() {
+        let mut rng = rand::rng();
+        let a = BigUint::random(&mut rng, 3000);
+        let b = BigUint::random(&mut rng, 2500);
+        assert_eq!(a.mul_ntt(&b), &a * &b);
+        let big = BigUint::random(&mut rng, 9 * NTT_THRESHOLD + 50);
+        let square = &big * &big;
+        assert_eq!(square.div_rem_small(7).1, big.div_rem_small(7).1.pow(2) % 7);
+    }
+}
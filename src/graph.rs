@@ -1,5 +1,7 @@
 use std::{collections::{hash_map::Entry, HashMap}, fmt};
-use rand::{distr::{weighted::WeightedIndex, Distribution}, rng, rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{rng, rngs::ThreadRng, seq::SliceRandom, Rng};
+#[cfg(feature = "polygon")]
+use crate::polygon::{random_points, Point};
 
 #[derive(Clone, Debug)]
 pub struct Edge {
@@ -7,17 +9,29 @@ pub struct Edge {
     v: usize,
     w: i64,
     weighted: bool,
+    attrs: Vec<i64>,
 }
 
 impl Edge {
     pub fn new(u: usize, v: usize, w: Option<i64>) -> Edge {
         if let Some(w) = w {
-            Edge { u, v, w, weighted: true }
+            Edge { u, v, w, weighted: true, attrs: Vec::new() }
         } else {
-            Edge { u, v, w: 0, weighted: false }
+            Edge { u, v, w: 0, weighted: false, attrs: Vec::new() }
         }
     }
 
+    /// The same edge carrying extra attribute columns after the weight,
+    /// e.g. `(capacity, cost)` or `(length, time)`.
+    pub fn with_attrs(mut self, attrs: Vec<i64>) -> Edge {
+        self.attrs = attrs;
+        self
+    }
+
+    pub fn attrs(&self) -> &[i64] {
+        &self.attrs
+    }
+
     pub fn is_weighted(&self) -> bool {
         self.weighted
     }
@@ -39,11 +53,16 @@ impl Edge {
     }
 
     pub fn format_default(&self) -> String {
-        if self.weighted {
+        let base = if self.weighted {
             self.format_weighted()
         } else {
             self.format_unweighted()
+        };
+        if self.attrs.is_empty() {
+            return base;
         }
+        let attrs: Vec<String> = self.attrs.iter().map(|a| a.to_string()).collect();
+        format!("{} {}", base, attrs.join(" "))
     }
 }
 
@@ -67,31 +86,31 @@ impl From<Edge> for (usize, usize) {
 
 impl From<(usize, usize)> for Edge {
     fn from(value: (usize, usize)) -> Self {
-        Edge { u: value.0, v: value.1, w: 0, weighted: false }
+        Edge { u: value.0, v: value.1, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(usize, usize, i64)> for Edge {
     fn from(value: (usize, usize, i64)) -> Self {
-        Edge { u: value.0, v: value.1, w: value.2, weighted: true }
+        Edge { u: value.0, v: value.1, w: value.2, weighted: true, attrs: Vec::new() }
     }
 }
 
 impl Into<Edge> for (usize, usize) {
     fn into(self) -> Edge {
-        Edge { u: self.0, v: self.1, w: 0, weighted: false }
+        Edge { u: self.0, v: self.1, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(u64, u64)> for Edge {
     fn from(value: (u64, u64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(u64, u64, i64)> for Edge {
     fn from(value: (u64, u64, i64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true, attrs: Vec::new() }
     }
 }
 
@@ -101,20 +120,21 @@ impl Into<Edge> for (u64, u64) {
             u: self.0 as usize,
             v: self.1 as usize,
             w: 0,
-            weighted: false
+            weighted: false,
+            attrs: Vec::new(),
         }
     }
 }
 
 impl From<(u32, u32)> for Edge {
     fn from(value: (u32, u32)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(u32, u32, i64)> for Edge {
     fn from(value: (u32, u32, i64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true, attrs: Vec::new() }
     }
 }
 
@@ -126,19 +146,20 @@ impl Into<Edge> for (u32, u32) {
             v: self.1 as usize,
             w: 0,
             weighted: false,
+            attrs: Vec::new(),
         }
     }
 }
 
 impl From<(isize, isize)> for Edge {
     fn from(value: (isize, isize)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(isize, isize, i64)> for Edge {
     fn from(value: (isize, isize, i64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true, attrs: Vec::new() }
     }
 }
 
@@ -149,19 +170,20 @@ impl Into<Edge> for (isize, isize) {
             v: self.1 as usize,
             w: 0,
             weighted: false,
+            attrs: Vec::new(),
         }
     }
 }
 
 impl From<(i64, i64)> for Edge {
     fn from(value: (i64, i64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(i64, i64, i64)> for Edge {
     fn from(value: (i64, i64, i64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true, attrs: Vec::new() }
     }
 }
 
@@ -172,19 +194,20 @@ impl Into<Edge> for (i64, i64) {
             v: self.1 as usize,
             w: 0,
             weighted: false,
+            attrs: Vec::new(),
         }
     }
 }
 
 impl From<(i32, i32)> for Edge {
     fn from(value: (i32, i32)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: 0, weighted: false, attrs: Vec::new() }
     }
 }
 
 impl From<(i32, i32, i64)> for Edge {
     fn from(value: (i32, i32, i64)) -> Self {
-        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true }
+        Edge { u: value.0 as usize, v: value.1 as usize, w: value.2, weighted: true, attrs: Vec::new() }
     }
 }
 
@@ -195,6 +218,7 @@ impl Into<Edge> for (i32, i32) {
             v: self.1 as usize,
             w: 0,
             weighted: false,
+            attrs: Vec::new(),
         }
     }
 }
@@ -203,6 +227,24 @@ impl Into<Edge> for (i32, i32) {
 pub struct SwitchGraph {
     directed: bool,
     edges: HashMap<(usize, usize), usize>,
+    // One normalized entry per edge, so a random edge can be picked in O(1),
+    // and where each normalized edge sits in that list.
+    edge_list: Vec<(usize, usize)>,
+    positions: HashMap<(usize, usize), Vec<usize>>,
+    // Undirected neighbour lists, for local connectivity checks.
+    adjacency: HashMap<usize, Vec<usize>>,
+}
+
+/// Options for randomizing a graph with edge switches.
+#[derive(Debug, Clone, Default)]
+pub struct SwitchOptions {
+    /// Number of switch attempts to run.
+    pub steps: usize,
+    /// Undo any switch that splits a connected component, so a connected
+    /// graph stays connected and separate components can only merge. Each
+    /// attempt then searches outward from the rewired endpoints until they
+    /// meet, which is usually far cheaper than a full connectivity check.
+    pub keep_connected: bool,
 }
 
 impl SwitchGraph {
@@ -215,6 +257,9 @@ impl SwitchGraph {
         let mut graph = SwitchGraph {
             directed,
             edges: HashMap::new(),
+            edge_list: Vec::new(),
+            positions: HashMap::new(),
+            adjacency: HashMap::new(),
         };
 
         for (u, v) in edges.into_iter().map(|e: E| { Into::<Edge>::into(e).into() }) {
@@ -230,14 +275,63 @@ impl SwitchGraph {
         if !self.directed && u != v {
             self.insert_single(v, u);
         }
+
+        let key = self.normalize(u, v);
+        self.positions.entry(key).or_default().push(self.edge_list.len());
+        self.edge_list.push(key);
+
+        self.adjacency.entry(u).or_default().push(v);
+        if u != v {
+            self.adjacency.entry(v).or_default().push(u);
+        }
     }
 
     pub fn remove(&mut self, u: usize, v: usize) {
+        if !self.edges.contains_key(&(u, v)) {
+            return;
+        }
         self.remove_single(u, v);
         
         if !self.directed && u != v {
             self.remove_single(v, u);
         }
+
+        let key = self.normalize(u, v);
+        let index = self.positions.get_mut(&key).and_then(|p| p.pop()).unwrap();
+        if self.positions[&key].is_empty() {
+            self.positions.remove(&key);
+        }
+        let last = self.edge_list.len() - 1;
+        self.edge_list.swap_remove(index);
+        if index != last {
+            let moved = self.edge_list[index];
+            for p in self.positions.get_mut(&moved).unwrap().iter_mut() {
+                if *p == last {
+                    *p = index;
+                }
+            }
+        }
+
+        self.remove_neighbor(u, v);
+        if u != v {
+            self.remove_neighbor(v, u);
+        }
+    }
+
+    fn remove_neighbor(&mut self, u: usize, v: usize) {
+        if let Entry::Occupied(mut entry) = self.adjacency.entry(u) {
+            let list = entry.get_mut();
+            if let Some(pos) = list.iter().position(|&x| x == v) {
+                list.swap_remove(pos);
+            }
+            if list.is_empty() {
+                entry.remove();
+            }
+        }
+    }
+
+    fn normalize(&self, u: usize, v: usize) -> (usize, usize) {
+        if self.directed { (u, v) } else { (u.min(v), u.max(v)) }
     }
 
     fn insert_single(&mut self, u: usize, v: usize) {
@@ -253,69 +347,54 @@ impl SwitchGraph {
         }
     }
 
+    /// Pick two random edges `(x1, y1)`, `(x2, y2)` and replace them with
+    /// `(x1, y2)`, `(x2, y1)`, keeping every degree. Undirected edges are
+    /// oriented at random first. Returns whether the switch was applied.
     pub fn switch(&mut self, self_loop: bool, repeated_edges: bool) -> bool {
-        let normalized_edges: Vec<((usize, usize), usize)> = self.get_normalized_edges();
-        
-        if normalized_edges.len() < 2 {
-            return false;
-        }
+        self.try_switch(self_loop, repeated_edges).is_some()
+    }
 
-        let weights: Vec<usize> = normalized_edges.iter().map(|(_, count)| *count).collect();
-        let total_weight: usize = weights.iter().sum();
-        
-        if total_weight < 2 {
-            return false;
+    /// Like [`SwitchGraph::switch`], but returns the two removed edges so the
+    /// switch can be undone.
+    fn try_switch(
+        &mut self,
+        self_loop: bool,
+        repeated_edges: bool,
+    ) -> Option<((usize, usize), (usize, usize))> {
+        let edge_count = self.edge_list.len();
+        if edge_count < 2 {
+            return None;
         }
 
         let mut rng = rng();
-        
-        let first_index = WeightedIndex::new(&weights)
-            .ok()
-            .map(|dist| dist.sample(&mut rng))
-            .unwrap_or(0);
-        let &(mut e1, _) = &normalized_edges[first_index];
-
-        let mut second_index = first_index;
-        if normalized_edges.len() > 1 {
-            for _ in 0..5 {
-                second_index = WeightedIndex::new(&weights)
-                    .ok()
-                    .map(|dist| dist.sample(&mut rng))
-                    .unwrap_or(first_index);
-                if second_index != first_index {
-                    break;
-                }
-            }
-            if second_index == first_index {
-                second_index = (first_index + 1) % normalized_edges.len();
-            }
+        let first_index = rng.random_range(0..edge_count);
+        let mut second_index = rng.random_range(0..edge_count - 1);
+        if second_index >= first_index {
+            second_index += 1;
         }
-        let &(mut e2, _) = &normalized_edges[second_index];
 
-        if !self.directed {
-            e1 = (e1.0.min(e1.1), e1.0.max(e1.1));
-            e2 = (e2.0.min(e2.1), e2.0.max(e2.1));
+        let (x1, y1) = self.edge_list[first_index];
+        let (mut x2, mut y2) = self.edge_list[second_index];
+        if !self.directed && rng.random_bool(0.5) {
+            std::mem::swap(&mut x2, &mut y2);
         }
 
-        let (x1, y1) = e1;
-        let (x2, y2) = e2;
-
         if self_loop {
             if x1 == x2 || y1 == y2 {
-                return false;
+                return None;
             }
         } else {
             let set1 = [x1, y1];
             let set2 = [x2, y2];
             if set1.iter().any(|v| set2.contains(v)) {
-                return false;
+                return None;
             }
         }
 
         if !repeated_edges
             && (self.edges.contains_key(&(x1, y2)) || self.edges.contains_key(&(x2, y1)))
         {
-            return false;
+            return None;
         }
 
         self.remove(x1, y1);
@@ -323,20 +402,96 @@ impl SwitchGraph {
         self.remove(x2, y2);
         self.insert(x2, y1);
 
-        true
+        Some(((x1, y1), (x2, y2)))
     }
 
-    fn get_normalized_edges(&self) -> Vec<((usize, usize), usize)> {
-        self.edges
-            .iter()
-            .map(|(&(u, v), &count)| {
-                if self.directed {
-                    ((u, v), count)
-                } else {
-                    ((u.min(v), u.max(v)), count)
+    /// Run `options.steps` switch attempts and return how many were applied.
+    /// With `keep_connected`, switches that disconnect the graph are undone,
+    /// so a connected graph stays connected.
+    pub fn mix(&mut self, options: &SwitchOptions, self_loop: bool, repeated_edges: bool) -> usize {
+        let mut applied = 0;
+        for _ in 0..options.steps {
+            let Some(((x1, y1), (x2, y2))) = self.try_switch(self_loop, repeated_edges) else {
+                continue;
+            };
+            // Every other edge is untouched, so no component splits as long
+            // as the endpoints of both removed edges still reach each other.
+            if options.keep_connected
+                && !(self.reachable(x1, y1) && self.reachable(x2, y2))
+            {
+                self.remove(x1, y2);
+                self.remove(x2, y1);
+                self.insert(x1, y1);
+                self.insert(x2, y2);
+                continue;
+            }
+            applied += 1;
+        }
+        applied
+    }
+
+    /// Whether `a` and `b` are (weakly) connected. Two breadth-first searches
+    /// run in lockstep from both ends, so nearby endpoints are found quickly
+    /// and, when they are not connected, the cost is bounded by the smaller
+    /// of the two components.
+    fn reachable(&self, a: usize, b: usize) -> bool {
+        if a == b {
+            return true;
+        }
+        // The side (0 from `a`, 1 from `b`) that reached each node first.
+        let mut side_of: HashMap<usize, usize> = HashMap::from([(a, 0), (b, 1)]);
+        let mut queues = [
+            std::collections::VecDeque::from([a]),
+            std::collections::VecDeque::from([b]),
+        ];
+        loop {
+            for (side, queue) in queues.iter_mut().enumerate() {
+                let Some(u) = queue.pop_front() else {
+                    return false;
+                };
+                for &v in self.adjacency.get(&u).into_iter().flatten() {
+                    match side_of.entry(v) {
+                        Entry::Occupied(entry) => {
+                            if *entry.get() != side {
+                                return true;
+                            }
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(side);
+                            queue.push_back(v);
+                        }
+                    }
                 }
-            })
-            .collect()
+            }
+        }
+    }
+
+    /// Whether the nodes touched by at least one edge form a single
+    /// (weakly) connected component.
+    pub fn is_connected(&self) -> bool {
+        self.component_count() <= 1
+    }
+
+    /// Number of (weakly) connected components among the nodes touched by at
+    /// least one edge.
+    fn component_count(&self) -> usize {
+        let mut seen: std::collections::HashSet<usize> = std::collections::HashSet::new();
+        let mut components = 0;
+        for &start in self.adjacency.keys() {
+            if !seen.insert(start) {
+                continue;
+            }
+            components += 1;
+            let mut stack = vec![start];
+            while let Some(u) = stack.pop() {
+                for &v in &self.adjacency[&u] {
+                    if seen.insert(v) {
+                        stack.push(v);
+                    }
+                }
+            }
+        }
+        components
     }
 
     pub fn from_directed_degree_sequence(
@@ -511,6 +666,31 @@ impl SwitchGraph {
         Ok(())
     }
 
+    /// Erdős–Gallai test: whether a simple undirected graph with this degree
+    /// sequence exists.
+    pub fn is_graphical(degree_sequence: &[usize]) -> bool {
+        let mut degrees = degree_sequence.to_vec();
+        degrees.sort_unstable_by(|a, b| b.cmp(a));
+        let n = degrees.len();
+        let mut prefix = vec![0usize; n + 1];
+        for i in 0..n {
+            prefix[i + 1] = prefix[i] + degrees[i];
+        }
+        if !prefix[n].is_multiple_of(2) {
+            return false;
+        }
+
+        for k in 1..=n {
+            // degrees[k..] is sorted descending, so the ones >= k come first.
+            let split = k + degrees[k..].partition_point(|&d| d >= k);
+            let rest = k * (split - k) + (prefix[n] - prefix[split]);
+            if prefix[k] > k * (k - 1) + rest {
+                return false;
+            }
+        }
+        true
+    }
+
     pub fn from_undirected_degree_sequence_simple(
         degree_sequence: &[usize],
     ) -> Result<Self, &'static str> {
@@ -539,6 +719,7 @@ impl SwitchGraph {
 pub struct Graph {
     directed: bool,
     edges: HashMap<usize, Vec<Edge>>,
+    node_values: HashMap<usize, i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -556,6 +737,8 @@ pub struct GraphGenOptions {
     pub directed: bool,
     pub self_loop: bool,
     pub repeated_edges: bool,
+    pub weight_mode: WeightMode,
+    pub weight_order: WeightOrder,
 }
 
 impl Default for GraphGenOptions {
@@ -564,20 +747,84 @@ impl Default for GraphGenOptions {
             directed: false,
             self_loop: false,
             repeated_edges: false,
+            weight_mode: WeightMode::Free,
+            weight_order: WeightOrder::Any,
         }
     }
 }
 
+/// Which values the edge weights of a generated graph may take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeightMode {
+    /// Weights come from `weight_limit` / `weight_gen` as usual.
+    #[default]
+    Free,
+    /// Pairwise distinct weights from `weight_limit`, so the MST is unique.
+    Distinct,
+    /// The weights are a permutation of `1..=edge_count`.
+    Permutation,
+}
+
+/// How edge weights are arranged, with edges ordered by their endpoints
+/// `(min(u, v), max(u, v))`. For a chain this is the order along the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeightOrder {
+    #[default]
+    Any,
+    Increasing,
+    Decreasing,
+}
+
 pub enum DegreeSequence<'a> {
     Directed(&'a [(usize, usize)]),
     Undirected(&'a [usize]),
 }
 
+/// Which kind of cactus `Graph::cactus` builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CactusKind {
+    /// Every vertex lies on at most one cycle.
+    Vertex,
+    /// Every edge lies on at most one cycle; cycles may share vertices.
+    Edge,
+}
+
+/// Shape of one tree of a forest, taking the `chain` and `flower` ratios of
+/// `Graph::tree`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TreeShape {
+    pub chain: f64,
+    pub flower: f64,
+}
+
+/// A generated forest together with the layout of its trees. Tree `i` has
+/// root `roots[i]`, `sizes[i]` nodes and height `depths[i]`.
+pub struct Forest {
+    pub graph: Graph,
+    /// `parent[v]` for `v` in `1..=n`, `0` for roots; `parent[0]` is unused.
+    pub parent: Vec<usize>,
+    pub roots: Vec<usize>,
+    pub sizes: Vec<usize>,
+    pub depths: Vec<usize>,
+}
+
+impl Forest {
+    /// Parents of nodes `1..=n` on one line, `0` marking roots.
+    pub fn parent_line(&self) -> String {
+        self.parent[1..]
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 impl Graph {
     pub fn new(point_count: usize, directed: bool) -> Graph {
         let mut graph = Graph {
             directed,
             edges: HashMap::new(),
+            node_values: HashMap::new(),
         };
 
         for point in 1..=point_count {
@@ -595,6 +842,7 @@ impl Graph {
         let mut graph = Graph {
             directed,
             edges: HashMap::new(),
+            node_values: HashMap::new(),
         };
         for node in nodes {
             graph.edges.insert(node, Vec::new());
@@ -627,7 +875,7 @@ impl Graph {
         let mut max_degree = 0usize;
         let mut total_degree = 0usize;
 
-        for (_node, edges) in &self.edges {
+        for edges in self.edges.values() {
             let degree = edges.len();
             min_degree = min_degree.min(degree);
             max_degree = max_degree.max(degree);
@@ -695,6 +943,75 @@ impl Graph {
 
         (nodes, matrix)
     }
+
+    /// Same as [`GraphMatrix::from_graph`].
+    pub fn to_graph_matrix(&self, no_edge: i64) -> GraphMatrix<i64> {
+        GraphMatrix::from_graph(self, no_edge)
+    }
+
+    pub fn set_node_value(&mut self, node: usize, value: i64) {
+        self.node_values.insert(node, value);
+    }
+
+    pub fn node_value(&self, node: usize) -> Option<i64> {
+        self.node_values.get(&node).copied()
+    }
+
+    /// Give every node a value (weight, color, ...) from `value_gen`, in
+    /// increasing node order. Edge weight generators such as
+    /// [`Graph::weight_uniform`] work here too.
+    pub fn assign_node_values<F>(&mut self, mut value_gen: F)
+    where
+        F: FnMut(&mut ThreadRng) -> i64,
+    {
+        let mut rng = rng();
+        let mut nodes: Vec<usize> = self.edges.keys().cloned().collect();
+        nodes.sort_unstable();
+        for node in nodes {
+            self.node_values.insert(node, value_gen(&mut rng));
+        }
+    }
+
+    /// Node values in increasing node order, space separated. Nodes without
+    /// a value print as 0.
+    pub fn node_values_line(&self) -> String {
+        let mut nodes: Vec<usize> = self.edges.keys().cloned().collect();
+        nodes.sort_unstable();
+        let values: Vec<String> = nodes
+            .iter()
+            .map(|&node| self.node_value(node).unwrap_or(0).to_string())
+            .collect();
+        values.join(" ")
+    }
+
+    /// Append one attribute column from `attr_gen` to every edge; both
+    /// directions of an undirected edge get the same value.
+    pub fn assign_edge_attrs<F>(&mut self, mut attr_gen: F)
+    where
+        F: FnMut(&mut ThreadRng) -> i64,
+    {
+        let mut rng = rng();
+        let edges: Vec<Edge> = self
+            .iter_edges()
+            .map(|e| {
+                let mut edge = e.clone();
+                edge.attrs.push(attr_gen(&mut rng));
+                edge
+            })
+            .collect();
+        *self = self.rebuild(edges);
+    }
+
+    /// Same nodes and node values, with `edges` as the edge list.
+    fn rebuild(&self, edges: Vec<Edge>) -> Graph {
+        let mut graph = Graph::with_nodes(self.edges.keys().cloned(), self.directed);
+        graph.node_values = self.node_values.clone();
+        for edge in edges {
+            let w = edge.weight();
+            graph.add_edge_with_attrs(edge.u, edge.v, w, edge.attrs);
+        }
+        graph
+    }
 }
 
 impl Graph {
@@ -727,10 +1044,11 @@ impl Graph {
     }
     
     pub fn add_single_edge(&mut self, u: usize, v: usize, w: Option<i64>) {
-        self.edges
-            .entry(u)
-            .and_modify(|g| { g.push(Edge::new(u, v, w)) })
-            .or_insert(vec![Edge::new(u, v, w)]);
+        self.push_edge(Edge::new(u, v, w));
+    }
+
+    fn push_edge(&mut self, edge: Edge) {
+        self.edges.entry(edge.u).or_default().push(edge);
     }
 
     fn add_directed_edge(&mut self, u: usize, v: usize, w: Option<i64>) {
@@ -752,6 +1070,14 @@ impl Graph {
         }
     }
 
+    /// Add an edge carrying extra attribute columns after its weight.
+    pub fn add_edge_with_attrs(&mut self, u: usize, v: usize, w: Option<i64>, attrs: Vec<i64>) {
+        if !self.directed && u != v {
+            self.push_edge(Edge::new(v, u, w).with_attrs(attrs.clone()));
+        }
+        self.push_edge(Edge::new(u, v, w).with_attrs(attrs));
+    }
+
     pub fn add_edges<I, E>(&mut self, edges: I)
     where
         I: IntoIterator<Item = E>,
@@ -841,9 +1167,17 @@ impl Graph {
         if shuffle {
             let mut new_node_id: Vec<usize> = (1..=self.edges.keys().count()).collect();
             new_node_id.shuffle(&mut rng);
+            if !self.node_values.is_empty() {
+                let mut values = vec![0; new_node_id.len()];
+                for (old, &new) in new_node_id.iter().enumerate() {
+                    values[new - 1] = self.node_value(old + 1).unwrap_or(0);
+                }
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                buf.push(values.join(" "));
+            }
             let mut edge_buf: Vec<Edge> = Vec::new();
             for edge in self.iter_edges() {
-                edge_buf.push(Edge::new(new_node_id[edge.u - 1], new_node_id[edge.v - 1], if edge.weighted { Some(edge.w) } else { None }));
+                edge_buf.push(Edge { u: new_node_id[edge.u - 1], v: new_node_id[edge.v - 1], ..edge.clone() });
             }
             edge_buf.shuffle(&mut rng);
             // for edge in edge_buf {
@@ -864,6 +1198,9 @@ impl Graph {
                 buf.push(edge_display_function(edge));
             }
         } else {
+            if !self.node_values.is_empty() {
+                buf.push(self.node_values_line());
+            }
             for edge in self.iter_edges() {
                 buf.push(edge_display_function(edge));
             }
@@ -898,11 +1235,13 @@ impl Graph {
             .collect();
 
         let mut graph = Graph::with_nodes(new_nodes, self.directed);
+        for (node, &value) in &self.node_values {
+            graph.node_values.insert(mapping[node], value);
+        }
         for edge in self.iter_edges_all() {
             let u = mapping[&edge.u];
             let v = mapping[&edge.v];
-            let w = if edge.weighted { Some(edge.w) } else { None };
-            graph.add_single_edge(u, v, w);
+            graph.push_edge(Edge { u, v, ..edge.clone() });
         }
 
         graph
@@ -914,9 +1253,7 @@ impl Graph {
         if !self.directed {
             for edge in edges.iter_mut() {
                 if rng.random_bool(0.5) {
-                    let tmp = edge.u;
-                    edge.u = edge.v;
-                    edge.v = tmp;
+                    std::mem::swap(&mut edge.u, &mut edge.v);
                 }
             }
         }
@@ -932,11 +1269,14 @@ impl Graph {
             nodes.insert(f(node));
         }
         let mut graph = Graph::with_nodes(nodes.into_iter(), self.directed);
-        for edge in self.iter_edges_all() {
+        for (&node, &value) in &self.node_values {
+            graph.node_values.insert(f(node), value);
+        }
+        for edge in self.iter_edges() {
             let u = f(edge.u);
             let v = f(edge.v);
             let weight = if edge.weighted { Some(edge.w) } else { None };
-            graph.add_edge(u, v, weight);
+            graph.add_edge_with_attrs(u, v, weight, edge.attrs.clone());
         }
         graph
     }
@@ -947,10 +1287,15 @@ impl Graph {
 
     pub fn subgraph_by_nodes(&self, nodes: &std::collections::HashSet<usize>) -> Graph {
         let mut graph = Graph::with_nodes(nodes.iter().cloned(), self.directed);
-        for edge in self.iter_edges_all() {
+        for (&node, &value) in &self.node_values {
+            if nodes.contains(&node) {
+                graph.node_values.insert(node, value);
+            }
+        }
+        for edge in self.iter_edges() {
             if nodes.contains(&edge.u) && nodes.contains(&edge.v) {
                 let weight = if edge.weighted { Some(edge.w) } else { None };
-                graph.add_edge(edge.u, edge.v, weight);
+                graph.add_edge_with_attrs(edge.u, edge.v, weight, edge.attrs.clone());
             }
         }
         graph
@@ -962,10 +1307,11 @@ impl Graph {
     {
         let nodes: Vec<usize> = self.edges.keys().cloned().collect();
         let mut graph = Graph::with_nodes(nodes, self.directed);
-        for edge in self.iter_edges_all() {
+        graph.node_values = self.node_values.clone();
+        for edge in self.iter_edges() {
             if predicate(edge) {
                 let weight = if edge.weighted { Some(edge.w) } else { None };
-                graph.add_edge(edge.u, edge.v, weight);
+                graph.add_edge_with_attrs(edge.u, edge.v, weight, edge.attrs.clone());
             }
         }
         graph
@@ -994,9 +1340,7 @@ impl Graph {
         for edges in self.edges.values_mut() {
             for edge in edges.iter_mut() {
                 if edge.u > edge.v {
-                    let tmp = edge.u;
-                    edge.u = edge.v;
-                    edge.v = tmp;
+                    std::mem::swap(&mut edge.u, &mut edge.v);
                 }
             }
         }
@@ -1021,110 +1365,594 @@ impl Graph {
     }
 }
 
-impl fmt::Display for Graph {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string(false, None, None))
-    }
+/// Graph relabelled to `0..nodes.len()` for the analysis routines. Each edge
+/// keeps one id, shared by both directions of an undirected edge.
+struct AdjacencyIndex {
+    nodes: Vec<usize>,
+    index: HashMap<usize, usize>,
+    adj: Vec<Vec<(usize, i64, usize)>>,
 }
 
-// impl Graph {
-//     fn tree(
-//         point_count: usize,
-//         chain: f64,
-//         flower: f64,
-//         weight_limit: (i64, i64),
-//         directed: bool,
-//         weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
-//         father_gen: Option<Box<dyn FnMut(&mut ThreadRng, usize) -> usize>>
-//     ) -> Graph {
-//         todo!()
-//     }
-// }
-
 impl Graph {
-    pub fn tree(
-        point_count: usize,
-        chain: f64,
-        flower: f64,
-        weight_limit: Option<(i64, i64)>,
-        directed: bool,
-        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
-        father_gen: Option<Box<dyn FnMut(&mut ThreadRng, usize) -> usize>>,
-    ) -> Graph {
-        assert!(
-            (0.0..=1.0).contains(&chain) && (0.0..=1.0).contains(&flower),
-            "chain and flower must be between 0.0 and 1.0"
-        );
-        assert!(
-            chain + flower <= 1.0,
-            "chain plus flower must be less than or equal to 1.0"
-        );
-        
-        let mut rng = rng();
-        let is_unweighted = weight_limit.is_none();
-        let use_custom_weight_gen = weight_gen.is_some();
-        let default_weight_gen = |rng: &mut ThreadRng| {
-            let (min_weight, max_weight) = weight_limit.unwrap();
-            rng.random_range(min_weight..=max_weight)
-        };
-        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
-        
-        let default_father_gen = |rng: &mut ThreadRng, cur| {
-            if cur <= 1 {
-                1
-            } else {
-                rng.random_range(1..cur)
+    /// Index the graph; with `symmetric`, directed edges are also followed
+    /// backwards. Unweighted edges count as weight 1.
+    fn adjacency_index(&self, symmetric: bool) -> AdjacencyIndex {
+        let mut nodes: Vec<usize> = self
+            .edges
+            .keys()
+            .cloned()
+            .chain(self.iter_edges_all().map(|e| e.v))
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        let index: HashMap<usize, usize> = nodes.iter().enumerate().map(|(i, &node)| (node, i)).collect();
+
+        let mut adj = vec![Vec::new(); nodes.len()];
+        for (id, edge) in self.iter_edges().enumerate() {
+            let (u, v) = (index[&edge.u], index[&edge.v]);
+            let w = edge.weight().unwrap_or(1);
+            adj[u].push((v, w, id));
+            if (symmetric || !self.directed) && u != v {
+                adj[v].push((u, w, id));
             }
-        };
-        let mut father_gen = father_gen.unwrap_or_else(|| Box::new(move |rng, cur| default_father_gen(rng, cur)));
-        
-        let total_edges = point_count.saturating_sub(1);
-        let chain_count = ((total_edges as f64) * chain).round() as usize;
-        let flower_count = ((total_edges as f64) * flower).round() as usize;
-        
-        let mut graph = Graph::new(point_count, directed);
-
-        let chain_end = chain_count + 1;
-        let flower_start = chain_end + 1;
-        let flower_end = (flower_start + flower_count).min(point_count + 1);
-
-        if !use_custom_weight_gen {
-            let chain_graph = Graph::chain(chain_end, weight_limit, directed, None);
-            graph.add_edges(chain_graph.iter_edges_all().cloned());
+        }
+        AdjacencyIndex { nodes, index, adj }
+    }
 
-            if flower_count > 0 {
-                let flower_graph = Graph::flower(flower_count + 1, weight_limit, directed, None);
-                let offset = chain_end.saturating_sub(1);
-                for edge in flower_graph.iter_edges_all() {
-                    let mut u = edge.u;
-                    let mut v = edge.v;
-                    if u != 1 { u += offset; }
-                    if v != 1 { v += offset; }
-                    let weight = if edge.weighted { Some(edge.w) } else { None };
-                    graph.add_edge(u, v, weight);
-                }
-            }
-        } else {
-            for i in 2..=chain_end {
-                let weight = if is_unweighted { None } else { Some(weight_gen(&mut rng)) };
-                graph.add_edge(i - 1, i, weight);
+    /// Connected components, ignoring edge directions. Each component is
+    /// sorted and components are ordered by their smallest node.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let AdjacencyIndex { nodes, adj, .. } = self.adjacency_index(true);
+        let mut seen = vec![false; nodes.len()];
+        let mut components = Vec::new();
+        for start in 0..nodes.len() {
+            if seen[start] {
+                continue;
             }
-
-            for i in flower_start..flower_end {
-                let weight = if is_unweighted { None } else { Some(weight_gen(&mut rng)) };
-                graph.add_edge(1, i, weight);
+            seen[start] = true;
+            let mut component = vec![start];
+            let mut head = 0;
+            while head < component.len() {
+                let u = component[head];
+                head += 1;
+                for &(v, _, _) in &adj[u] {
+                    if !seen[v] {
+                        seen[v] = true;
+                        component.push(v);
+                    }
+                }
             }
+            component.sort_unstable();
+            components.push(component.into_iter().map(|i| nodes[i]).collect());
         }
-        
-        let random_start = flower_end;
-        for i in random_start..=point_count {
-            if i == 1 { continue; }
-            let father = father_gen(&mut rng, i);
-            let weight = if is_unweighted { None } else { Some(weight_gen(&mut rng)) };
-            graph.add_edge(father, i, weight);
-        }
-        
-        graph
+        components
+    }
+
+    /// Whether the graph is connected, ignoring edge directions.
+    pub fn is_connected(&self) -> bool {
+        self.connected_components().len() <= 1
+    }
+
+    /// Strongly connected components (Tarjan), listed in topological order of
+    /// the condensation: edges between components only go forward. Each
+    /// component is sorted.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let AdjacencyIndex { nodes, adj, .. } = self.adjacency_index(false);
+        let n = nodes.len();
+        let mut order = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+
+        for start in 0..n {
+            if order[start] != usize::MAX {
+                continue;
+            }
+            let mut call_stack = vec![(start, 0)];
+            order[start] = counter;
+            low[start] = counter;
+            counter += 1;
+            stack.push(start);
+            on_stack[start] = true;
+
+            while let Some(&mut (u, ref mut pos)) = call_stack.last_mut() {
+                if let Some(&(v, _, _)) = adj[u].get(*pos) {
+                    *pos += 1;
+                    if order[v] == usize::MAX {
+                        order[v] = counter;
+                        low[v] = counter;
+                        counter += 1;
+                        stack.push(v);
+                        on_stack[v] = true;
+                        call_stack.push((v, 0));
+                    } else if on_stack[v] {
+                        low[u] = low[u].min(order[v]);
+                    }
+                    continue;
+                }
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    low[parent] = low[parent].min(low[u]);
+                }
+                if low[u] == order[u] {
+                    let mut component = Vec::new();
+                    while let Some(v) = stack.pop() {
+                        on_stack[v] = false;
+                        component.push(nodes[v]);
+                        if v == u {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+        // Tarjan finishes sink components first.
+        components.reverse();
+        components
+    }
+
+    /// Two-colouring of the graph ignoring edge directions, or `None` if it
+    /// has an odd cycle. The smallest node of each component goes to the
+    /// first side.
+    pub fn bipartition(&self) -> Option<(Vec<usize>, Vec<usize>)> {
+        let AdjacencyIndex { nodes, adj, .. } = self.adjacency_index(true);
+        let mut color: Vec<Option<bool>> = vec![None; nodes.len()];
+        for start in 0..nodes.len() {
+            if color[start].is_some() {
+                continue;
+            }
+            color[start] = Some(false);
+            let mut queue = vec![start];
+            while let Some(u) = queue.pop() {
+                let side = color[u]?;
+                for &(v, _, _) in &adj[u] {
+                    match color[v] {
+                        None => {
+                            color[v] = Some(!side);
+                            queue.push(v);
+                        }
+                        Some(c) if c == side => return None,
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for (i, c) in color.into_iter().enumerate() {
+            if c == Some(false) {
+                left.push(nodes[i]);
+            } else {
+                right.push(nodes[i]);
+            }
+        }
+        Some((left, right))
+    }
+
+    pub fn is_bipartite(&self) -> bool {
+        self.bipartition().is_some()
+    }
+
+    /// Some cycle of the graph as a node sequence `v0 -> v1 -> ... -> v0`
+    /// (directed cycles for a directed graph), or `None` if it is acyclic.
+    /// Self-loops and parallel undirected edges count as cycles.
+    pub fn find_cycle(&self) -> Option<Vec<usize>> {
+        let AdjacencyIndex { nodes, adj, .. } = self.adjacency_index(false);
+        let n = nodes.len();
+        // 0 = unvisited, 1 = on the DFS path, 2 = finished.
+        let mut state = vec![0u8; n];
+        let mut parent = vec![usize::MAX; n];
+
+        for start in 0..n {
+            if state[start] != 0 {
+                continue;
+            }
+            state[start] = 1;
+            let mut call_stack = vec![(start, usize::MAX, 0)];
+            while let Some(&mut (u, parent_edge, ref mut pos)) = call_stack.last_mut() {
+                let Some(&(v, _, id)) = adj[u].get(*pos) else {
+                    state[u] = 2;
+                    call_stack.pop();
+                    continue;
+                };
+                *pos += 1;
+                if !self.directed && id == parent_edge {
+                    continue;
+                }
+                match state[v] {
+                    0 => {
+                        state[v] = 1;
+                        parent[v] = u;
+                        call_stack.push((v, id, 0));
+                    }
+                    1 => {
+                        let mut cycle = vec![nodes[u]];
+                        let mut x = u;
+                        while x != v {
+                            x = parent[x];
+                            cycle.push(nodes[x]);
+                        }
+                        cycle.reverse();
+                        return Some(cycle);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.find_cycle().is_some()
+    }
+
+    /// Whether the graph, ignoring edge directions, is a tree.
+    pub fn is_tree(&self) -> bool {
+        let node_count = self.adjacency_index(true).nodes.len();
+        node_count > 0 && self.edge_count() == node_count - 1 && self.is_connected()
+    }
+
+    /// Eccentricity of `root`: the largest shortest-path distance (in edges)
+    /// from `root` to a node it reaches, ignoring edge directions. For a tree
+    /// this is its depth when rooted at `root`.
+    pub fn tree_depth(&self, root: usize) -> usize {
+        self.bfs_from(&self.adjacency_index(true), root).into_iter().flatten().max().unwrap_or(0)
+    }
+
+    /// Unweighted shortest distances (edge counts) from `source` to every
+    /// node it reaches.
+    pub fn bfs_distances(&self, source: usize) -> HashMap<usize, usize> {
+        let index = self.adjacency_index(false);
+        let dist = self.bfs_from(&index, source);
+        Graph::reached(&index.nodes, dist)
+    }
+
+    fn bfs_from(&self, index: &AdjacencyIndex, source: usize) -> Vec<Option<usize>> {
+        let mut dist = vec![None; index.nodes.len()];
+        let Some(&s) = index.index.get(&source) else {
+            return dist;
+        };
+        dist[s] = Some(0);
+        let mut queue = std::collections::VecDeque::from([s]);
+        while let Some(u) = queue.pop_front() {
+            let d = dist[u].unwrap_or(0) + 1;
+            for &(v, _, _) in &index.adj[u] {
+                if dist[v].is_none() {
+                    dist[v] = Some(d);
+                    queue.push_back(v);
+                }
+            }
+        }
+        dist
+    }
+
+    fn reached<T>(nodes: &[usize], dist: Vec<Option<T>>) -> HashMap<usize, T> {
+        nodes
+            .iter()
+            .zip(dist)
+            .filter_map(|(&node, d)| d.map(|d| (node, d)))
+            .collect()
+    }
+
+    /// Shortest distances from `source` with Dijkstra. Every edge weight must
+    /// be non-negative; unweighted edges count as 1.
+    pub fn dijkstra(&self, source: usize) -> HashMap<usize, i64> {
+        let AdjacencyIndex { nodes, index, adj } = self.adjacency_index(false);
+        assert!(
+            adj.iter().flatten().all(|&(_, w, _)| w >= 0),
+            "dijkstra requires non-negative weights"
+        );
+        let mut dist: Vec<Option<i64>> = vec![None; nodes.len()];
+        if let Some(&s) = index.get(&source) {
+            let mut heap = std::collections::BinaryHeap::from([std::cmp::Reverse((0i64, s))]);
+            dist[s] = Some(0);
+            while let Some(std::cmp::Reverse((d, u))) = heap.pop() {
+                if dist[u] != Some(d) {
+                    continue;
+                }
+                for &(v, w, _) in &adj[u] {
+                    if dist[v].is_none_or(|old| d + w < old) {
+                        dist[v] = Some(d + w);
+                        heap.push(std::cmp::Reverse((d + w, v)));
+                    }
+                }
+            }
+        }
+        Graph::reached(&nodes, dist)
+    }
+
+    /// Shortest distances from `source` with Bellman-Ford, or `None` if a
+    /// negative cycle is reachable from it. An undirected negative edge is
+    /// such a cycle.
+    pub fn bellman_ford(&self, source: usize) -> Option<HashMap<usize, i64>> {
+        let AdjacencyIndex { nodes, index, adj } = self.adjacency_index(false);
+        let mut dist: Vec<Option<i64>> = vec![None; nodes.len()];
+        if let Some(&s) = index.get(&source) {
+            dist[s] = Some(0);
+            for round in 0..nodes.len() {
+                let mut changed = false;
+                for u in 0..nodes.len() {
+                    let Some(d) = dist[u] else {
+                        continue;
+                    };
+                    for &(v, w, _) in &adj[u] {
+                        if dist[v].is_none_or(|old| d + w < old) {
+                            dist[v] = Some(d + w);
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+                if round + 1 == nodes.len() {
+                    return None;
+                }
+            }
+        }
+        Some(Graph::reached(&nodes, dist))
+    }
+
+    /// Largest unweighted distance between two nodes, or `None` if some node
+    /// cannot reach another. Trees take two BFS passes; other graphs take one
+    /// BFS per node.
+    pub fn diameter(&self) -> Option<usize> {
+        let index = self.adjacency_index(false);
+        let Some(&first) = index.nodes.first() else {
+            return Some(0);
+        };
+        if !self.directed && self.is_tree() {
+            let dist = self.bfs_from(&index, first);
+            let far = (0..dist.len()).max_by_key(|&i| dist[i])?;
+            return self.bfs_from(&index, index.nodes[far]).into_iter().max().flatten();
+        }
+        let mut best = 0;
+        for &node in &index.nodes {
+            let dist = self.bfs_from(&index, node);
+            for d in dist {
+                best = best.max(d?);
+            }
+        }
+        Some(best)
+    }
+
+    /// Edges whose removal disconnects the graph, ignoring edge directions.
+    /// Each bridge is reported as `(min, max)`, sorted.
+    pub fn bridges(&self) -> Vec<(usize, usize)> {
+        let (bridges, _) = self.low_links();
+        bridges
+    }
+
+    /// Nodes whose removal disconnects their component, ignoring edge
+    /// directions. Sorted.
+    pub fn articulation_points(&self) -> Vec<usize> {
+        let (_, points) = self.low_links();
+        points
+    }
+
+    fn low_links(&self) -> (Vec<(usize, usize)>, Vec<usize>) {
+        let AdjacencyIndex { nodes, adj, .. } = self.adjacency_index(true);
+        let n = nodes.len();
+        let mut tin = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut is_cut = vec![false; n];
+        let mut bridges = Vec::new();
+        let mut timer = 0;
+
+        for root in 0..n {
+            if tin[root] != usize::MAX {
+                continue;
+            }
+            tin[root] = timer;
+            low[root] = timer;
+            timer += 1;
+            let mut root_children = 0;
+            let mut call_stack = vec![(root, usize::MAX, 0)];
+            while let Some(&mut (u, parent_edge, ref mut pos)) = call_stack.last_mut() {
+                if let Some(&(v, _, id)) = adj[u].get(*pos) {
+                    *pos += 1;
+                    if id == parent_edge {
+                        continue;
+                    }
+                    if tin[v] == usize::MAX {
+                        tin[v] = timer;
+                        low[v] = timer;
+                        timer += 1;
+                        if u == root {
+                            root_children += 1;
+                        }
+                        call_stack.push((v, id, 0));
+                    } else {
+                        low[u] = low[u].min(tin[v]);
+                    }
+                    continue;
+                }
+                call_stack.pop();
+                if let Some(&(p, _, _)) = call_stack.last() {
+                    low[p] = low[p].min(low[u]);
+                    if low[u] > tin[p] {
+                        bridges.push((nodes[p].min(nodes[u]), nodes[p].max(nodes[u])));
+                    }
+                    if low[u] >= tin[p] && p != root {
+                        is_cut[p] = true;
+                    }
+                }
+            }
+            is_cut[root] = root_children > 1;
+        }
+
+        bridges.sort_unstable();
+        let points = (0..n).filter(|&i| is_cut[i]).map(|i| nodes[i]).collect();
+        (bridges, points)
+    }
+
+    /// Topological order of a directed graph, choosing the smallest available
+    /// node first, or `None` if it has a cycle.
+    pub fn topological_order(&self) -> Option<Vec<usize>> {
+        assert!(self.directed, "topological order needs a directed graph");
+        let AdjacencyIndex { nodes, adj, .. } = self.adjacency_index(false);
+        let mut in_degree = vec![0; nodes.len()];
+        for &(v, _, _) in adj.iter().flatten() {
+            in_degree[v] += 1;
+        }
+        let mut heap: std::collections::BinaryHeap<std::cmp::Reverse<usize>> = (0..nodes.len())
+            .filter(|&i| in_degree[i] == 0)
+            .map(std::cmp::Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(nodes.len());
+        while let Some(std::cmp::Reverse(u)) = heap.pop() {
+            order.push(nodes[u]);
+            for &(v, _, _) in &adj[u] {
+                in_degree[v] -= 1;
+                if in_degree[v] == 0 {
+                    heap.push(std::cmp::Reverse(v));
+                }
+            }
+        }
+        (order.len() == nodes.len()).then_some(order)
+    }
+
+    /// Heaviest path of a DAG (unweighted edges count as 1) as its total
+    /// weight and node sequence, or `None` if the graph has a cycle. The
+    /// path may start and end anywhere.
+    pub fn dag_longest_path(&self) -> Option<(i64, Vec<usize>)> {
+        let order = self.topological_order()?;
+        let AdjacencyIndex { nodes, index, adj } = self.adjacency_index(false);
+        let mut best = vec![0i64; nodes.len()];
+        let mut prev = vec![usize::MAX; nodes.len()];
+        for node in &order {
+            let u = index[node];
+            for &(v, w, _) in &adj[u] {
+                if best[u] + w > best[v] {
+                    best[v] = best[u] + w;
+                    prev[v] = u;
+                }
+            }
+        }
+
+        let Some(mut end) = (0..nodes.len()).max_by_key(|&i| best[i]) else {
+            return Some((0, Vec::new()));
+        };
+        let total = best[end];
+        let mut path = vec![nodes[end]];
+        while prev[end] != usize::MAX {
+            end = prev[end];
+            path.push(nodes[end]);
+        }
+        path.reverse();
+        Some((total, path))
+    }
+}
+
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string(false, None, None))
+    }
+}
+
+// impl Graph {
+//     fn tree(
+//         point_count: usize,
+//         chain: f64,
+//         flower: f64,
+//         weight_limit: (i64, i64),
+//         directed: bool,
+//         weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+//         father_gen: Option<Box<dyn FnMut(&mut ThreadRng, usize) -> usize>>
+//     ) -> Graph {
+//         todo!()
+//     }
+// }
+
+impl Graph {
+    pub fn tree(
+        point_count: usize,
+        chain: f64,
+        flower: f64,
+        weight_limit: Option<(i64, i64)>,
+        directed: bool,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+        father_gen: Option<Box<dyn FnMut(&mut ThreadRng, usize) -> usize>>,
+    ) -> Graph {
+        assert!(
+            (0.0..=1.0).contains(&chain) && (0.0..=1.0).contains(&flower),
+            "chain and flower must be between 0.0 and 1.0"
+        );
+        assert!(
+            chain + flower <= 1.0,
+            "chain plus flower must be less than or equal to 1.0"
+        );
+        
+        let mut rng = rng();
+        let is_unweighted = weight_limit.is_none();
+        let use_custom_weight_gen = weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.unwrap();
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+        
+        let default_father_gen = |rng: &mut ThreadRng, cur| {
+            if cur <= 1 {
+                1
+            } else {
+                rng.random_range(1..cur)
+            }
+        };
+        let mut father_gen = father_gen.unwrap_or_else(|| Box::new(move |rng, cur| default_father_gen(rng, cur)));
+        
+        let total_edges = point_count.saturating_sub(1);
+        let chain_count = ((total_edges as f64) * chain).round() as usize;
+        // Rounded separately, the two parts could add up to one edge too many.
+        let flower_count =
+            (((total_edges as f64) * flower).round() as usize).min(total_edges - chain_count);
+        
+        let mut graph = Graph::new(point_count, directed);
+
+        let chain_end = chain_count + 1;
+        let flower_start = chain_end + 1;
+        let flower_end = (flower_start + flower_count).min(point_count + 1);
+
+        if !use_custom_weight_gen {
+            let chain_graph = Graph::chain(chain_end, weight_limit, directed, None);
+            graph.add_edges(chain_graph.iter_edges().cloned());
+
+            if flower_count > 0 {
+                let flower_graph = Graph::flower(flower_count + 1, weight_limit, directed, None);
+                let offset = chain_end.saturating_sub(1);
+                for edge in flower_graph.iter_edges() {
+                    let mut u = edge.u;
+                    let mut v = edge.v;
+                    if u != 1 { u += offset; }
+                    if v != 1 { v += offset; }
+                    let weight = if edge.weighted { Some(edge.w) } else { None };
+                    graph.add_edge(u, v, weight);
+                }
+            }
+        } else {
+            for i in 2..=chain_end {
+                let weight = if is_unweighted { None } else { Some(weight_gen(&mut rng)) };
+                graph.add_edge(i - 1, i, weight);
+            }
+
+            for i in flower_start..flower_end {
+                let weight = if is_unweighted { None } else { Some(weight_gen(&mut rng)) };
+                graph.add_edge(1, i, weight);
+            }
+        }
+        
+        let random_start = flower_end;
+        for i in random_start..=point_count {
+            if i == 1 { continue; }
+            let father = father_gen(&mut rng, i);
+            let weight = if is_unweighted { None } else { Some(weight_gen(&mut rng)) };
+            graph.add_edge(father, i, weight);
+        }
+        
+        graph
     }
 
     pub fn binary_tree(
@@ -1376,7 +2204,7 @@ impl Graph {
         weight_limit: Option<(i64, i64)>,
         weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
     ) -> Graph {
-        Graph::graph(
+        let mut graph = Graph::graph(
             point_count,
             edge_count,
             options.directed,
@@ -1384,7 +2212,66 @@ impl Graph {
             options.repeated_edges,
             weight_limit,
             weight_gen,
-        )
+        );
+        if options.weight_mode != WeightMode::Free || options.weight_order != WeightOrder::Any {
+            graph.constrain_weights(options.weight_mode, options.weight_order, weight_limit);
+        }
+        graph
+    }
+
+    /// Reassign the edge weights to satisfy `mode`, then arrange them by
+    /// `order`. [`WeightMode::Free`] keeps the current weights, so only the
+    /// order changes; unweighted edges are left alone in that case.
+    pub fn constrain_weights(&mut self, mode: WeightMode, order: WeightOrder, weight_limit: Option<(i64, i64)>) {
+        let mut rng = rng();
+        let mut edges: Vec<Edge> = self.iter_edges().cloned().collect();
+        edges.sort_by_key(|e| (e.u.min(e.v), e.u.max(e.v)));
+
+        let mut weights: Vec<Option<i64>> = match mode {
+            WeightMode::Free => edges.iter().map(|e| e.weight()).collect(),
+            WeightMode::Distinct => {
+                let limit = weight_limit.expect("weight_limit required for distinct weights");
+                Graph::distinct_weights(&mut rng, edges.len(), limit).into_iter().map(Some).collect()
+            }
+            WeightMode::Permutation => (1..=edges.len() as i64).map(Some).collect(),
+        };
+        match order {
+            WeightOrder::Any => {
+                if mode != WeightMode::Free {
+                    weights.shuffle(&mut rng);
+                }
+            }
+            WeightOrder::Increasing => weights.sort(),
+            WeightOrder::Decreasing => weights.sort_by(|a, b| b.cmp(a)),
+        }
+
+        for (edge, w) in edges.iter_mut().zip(weights) {
+            edge.w = w.unwrap_or(0);
+            edge.weighted = w.is_some();
+        }
+        *self = self.rebuild(edges);
+    }
+
+    /// `count` distinct values from `weight_limit`, in random order.
+    fn distinct_weights(rng: &mut ThreadRng, count: usize, weight_limit: (i64, i64)) -> Vec<i64> {
+        let (min_w, max_w) = weight_limit;
+        let span = (max_w as i128 - min_w as i128 + 1).max(0) as u128;
+        assert!(count as u128 <= span, "weight_limit has fewer values than edges");
+        if span <= 2 * count as u128 {
+            let mut values: Vec<i64> = (min_w..=max_w).collect();
+            values.shuffle(rng);
+            values.truncate(count);
+            return values;
+        }
+        let mut seen = std::collections::HashSet::with_capacity(count);
+        let mut values = Vec::with_capacity(count);
+        while values.len() < count {
+            let w = rng.random_range(min_w..=max_w);
+            if seen.insert(w) {
+                values.push(w);
+            }
+        }
+        values
     }
 
     pub fn graph_with_weight_limit(
@@ -1454,16 +2341,211 @@ impl Graph {
         graph
     }
 
-    pub fn complete_bipartite(
-        left_count: usize,
-        right_count: usize,
-        directed: bool,
+    /// Random tournament: every pair of nodes is joined by one edge of a
+    /// random direction.
+    pub fn tournament(
+        point_count: usize,
         weight_limit: Option<(i64, i64)>,
         weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
     ) -> Graph {
-        assert!(left_count > 0 && right_count > 0, "partition sizes must be above zero");
+        assert!(point_count > 0, "point_count must be above zero");
         let mut rng = rng();
-        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let mut beats = vec![vec![false; point_count + 1]; point_count + 1];
+        Graph::orient_remaining(&mut beats, &mut rng);
+        Graph::from_beats(&beats, weight_limit, weight_gen)
+    }
+
+    /// Transitive tournament over a random ranking: `order[i]` beats
+    /// `order[j]` whenever `i < j`. Returns the graph and the ranking.
+    pub fn transitive_tournament(
+        point_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> (Graph, Vec<usize>) {
+        assert!(point_count > 0, "point_count must be above zero");
+        let mut order: Vec<usize> = (1..=point_count).collect();
+        order.shuffle(&mut rng());
+        let mut beats = vec![vec![false; point_count + 1]; point_count + 1];
+        for i in 0..point_count {
+            for j in i + 1..point_count {
+                beats[order[i]][order[j]] = true;
+            }
+        }
+        (Graph::from_beats(&beats, weight_limit, weight_gen), order)
+    }
+
+    /// Random tournament with a planted Hamiltonian cycle, so it is strongly
+    /// connected. Returns the graph and the cycle order.
+    pub fn hamiltonian_tournament(
+        point_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> (Graph, Vec<usize>) {
+        assert!(point_count >= 3, "a Hamiltonian cycle needs at least 3 nodes");
+        let mut rng = rng();
+        let mut order: Vec<usize> = (1..=point_count).collect();
+        order.shuffle(&mut rng);
+        let mut beats = vec![vec![false; point_count + 1]; point_count + 1];
+        for i in 0..point_count {
+            beats[order[i]][order[(i + 1) % point_count]] = true;
+        }
+        Graph::orient_remaining(&mut beats, &mut rng);
+        (Graph::from_beats(&beats, weight_limit, weight_gen), order)
+    }
+
+    /// Random tournament with a planted king that is not a source: the king
+    /// reaches every node in at most two steps, yet some nodes beat it.
+    /// Returns the graph and the king.
+    pub fn king_tournament(
+        point_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> (Graph, usize) {
+        assert!(point_count >= 3, "point_count must be at least 3");
+        let mut rng = rng();
+        let mut others: Vec<usize> = (1..=point_count).collect();
+        others.shuffle(&mut rng);
+        let king = others.pop().unwrap();
+        // The king beats `others[..split]` directly and reaches the rest
+        // through one of them.
+        let split = rng.random_range(1..others.len());
+        let (near, far) = others.split_at(split);
+        let mut beats = vec![vec![false; point_count + 1]; point_count + 1];
+        for &x in near {
+            beats[king][x] = true;
+        }
+        for &x in far {
+            beats[x][king] = true;
+            beats[near[rng.random_range(0..near.len())]][x] = true;
+        }
+        Graph::orient_remaining(&mut beats, &mut rng);
+        (Graph::from_beats(&beats, weight_limit, weight_gen), king)
+    }
+
+    /// Landau's condition: sorted ascending, the first `k` scores add up to
+    /// at least `k * (k - 1) / 2`, with equality when `k = n`.
+    pub fn is_score_sequence(scores: &[usize]) -> bool {
+        let mut sorted = scores.to_vec();
+        sorted.sort_unstable();
+        let mut sum = 0usize;
+        for (k, &s) in sorted.iter().enumerate() {
+            sum += s;
+            if sum < (k + 1) * k / 2 {
+                return false;
+            }
+        }
+        sum == sorted.len() * sorted.len().saturating_sub(1) / 2
+    }
+
+    /// Random tournament where node `i + 1` wins exactly `scores[i]` games.
+    /// A greedy construction is mixed by reversing random directed
+    /// triangles, which keeps every score.
+    pub fn tournament_from_scores(
+        scores: &[usize],
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        let n = scores.len();
+        assert!(n > 0, "scores must not be empty");
+        assert!(Graph::is_score_sequence(scores), "scores violate Landau's condition");
+        let mut rng = rng();
+        let mut beats = vec![vec![false; n + 1]; n + 1];
+
+        // Settle the weakest remaining node: it beats `s` of the others and
+        // loses to the strongest ones, which spend a win on it.
+        let mut remaining: Vec<(usize, usize)> = scores
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| (s, i + 1))
+            .collect();
+        while !remaining.is_empty() {
+            remaining.sort_unstable();
+            let (score, node) = remaining.remove(0);
+            let cut = score;
+            for entry in remaining[cut..].iter_mut() {
+                beats[entry.1][node] = true;
+                entry.0 -= 1;
+            }
+            for entry in &remaining[..cut] {
+                beats[node][entry.1] = true;
+            }
+        }
+
+        if n >= 3 {
+            for _ in 0..n * n {
+                let u = rng.random_range(1..=n);
+                let v = rng.random_range(1..=n);
+                let w = rng.random_range(1..=n);
+                if u == v || v == w || u == w || !beats[u][v] || !beats[v][w] || !beats[w][u] {
+                    continue;
+                }
+                beats[u][v] = false;
+                beats[v][w] = false;
+                beats[w][u] = false;
+                beats[v][u] = true;
+                beats[w][v] = true;
+                beats[u][w] = true;
+            }
+        }
+        Graph::from_beats(&beats, weight_limit, weight_gen)
+    }
+
+    /// Out-degree of every node `1..=node_count`, i.e. the score sequence of
+    /// a tournament.
+    pub fn score_sequence(&self) -> Vec<usize> {
+        (1..=self.node_count())
+            .map(|u| self.edges.get(&u).map_or(0, |list| list.len()))
+            .collect()
+    }
+
+    fn from_beats(
+        beats: &[Vec<bool>],
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut graph = Graph::new(beats.len() - 1, true);
+        for (u, row) in beats.iter().enumerate().skip(1) {
+            for (v, _) in row.iter().enumerate().filter(|&(_, &won)| won) {
+                let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+                graph.add_edge(u, v, weight);
+            }
+        }
+        graph
+    }
+
+    /// Give a random direction to every pair not yet decided in `beats`.
+    fn orient_remaining(beats: &mut [Vec<bool>], rng: &mut ThreadRng) {
+        let n = beats.len();
+        for (u, v) in (1..n).flat_map(|u| (u + 1..n).map(move |v| (u, v))) {
+            if beats[u][v] || beats[v][u] {
+                continue;
+            }
+            if rng.random_bool(0.5) {
+                beats[u][v] = true;
+            } else {
+                beats[v][u] = true;
+            }
+        }
+    }
+
+    pub fn complete_bipartite(
+        left_count: usize,
+        right_count: usize,
+        directed: bool,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(left_count > 0 && right_count > 0, "partition sizes must be above zero");
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
         let default_weight_gen = |rng: &mut ThreadRng| {
             let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
             rng.random_range(min_weight..=max_weight)
@@ -1494,6 +2576,211 @@ impl Graph {
         graph
     }
 
+    /// Random simple bipartite graph with `edge_count` edges. Left nodes are
+    /// `1..=left_count`, right nodes follow them, as in
+    /// [`Graph::complete_bipartite`].
+    pub fn bipartite(
+        left_count: usize,
+        right_count: usize,
+        edge_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        let left: Vec<usize> = (1..=left_count).collect();
+        Graph::bipartite_covered(
+            left_count,
+            right_count,
+            edge_count,
+            (left, Vec::new()),
+            Vec::new(),
+            weight_limit,
+            weight_gen,
+        )
+    }
+
+    /// Random simple bipartite graph whose maximum matching has exactly
+    /// `matching_size` edges.
+    ///
+    /// A matching of that size is planted, and every other edge touches a
+    /// vertex cover of the same size, so by König's theorem no larger
+    /// matching exists.
+    pub fn bipartite_with_matching(
+        left_count: usize,
+        right_count: usize,
+        edge_count: usize,
+        matching_size: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(
+            matching_size <= left_count.min(right_count),
+            "matching_size cannot exceed the smaller side"
+        );
+        assert!(edge_count >= matching_size, "edge_count too small for the matching");
+        let mut rng = rng();
+
+        // The cover takes `a` left and `k - a` right vertices; keep the splits
+        // that leave room for `edge_count` edges.
+        let k = matching_size;
+        let capacity = |a: usize| a * right_count + (k - a) * left_count - a * (k - a);
+        let splits: Vec<usize> = (0..=k).filter(|&a| capacity(a) >= edge_count).collect();
+        assert!(!splits.is_empty(), "edge_count too large for the requested matching_size");
+        let a = splits[rng.random_range(0..splits.len())];
+        let b = k - a;
+
+        let mut left: Vec<usize> = (1..=left_count).collect();
+        let mut right: Vec<usize> = (left_count + 1..=left_count + right_count).collect();
+        left.shuffle(&mut rng);
+        right.shuffle(&mut rng);
+        let cover_left = left[..a].to_vec();
+        let cover_right = right[..b].to_vec();
+
+        let mut matching = Vec::with_capacity(k);
+        for i in 0..a {
+            matching.push((left[i], right[b + i]));
+        }
+        for j in 0..b {
+            matching.push((left[a + j], right[j]));
+        }
+
+        Graph::bipartite_covered(
+            left_count,
+            right_count,
+            edge_count,
+            (cover_left, cover_right),
+            matching,
+            weight_limit,
+            weight_gen,
+        )
+    }
+
+    /// Random bipartite graph with `count` nodes on each side that has a
+    /// perfect matching.
+    pub fn bipartite_perfect_matching(
+        count: usize,
+        edge_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        Graph::bipartite_with_matching(count, count, edge_count, count, weight_limit, weight_gen)
+    }
+
+    /// Random simple `k`-regular bipartite graph with `count` nodes on each
+    /// side. It starts from `k` shifted perfect matchings under random labels
+    /// and is mixed with `switch_steps` calls to [`SwitchGraph::switch`].
+    pub fn regular_bipartite(
+        count: usize,
+        k: usize,
+        switch_steps: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(count > 0, "count must be above zero");
+        assert!(k <= count, "k cannot exceed count");
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut left: Vec<usize> = (1..=count).collect();
+        let mut right: Vec<usize> = (count + 1..=2 * count).collect();
+        left.shuffle(&mut rng);
+        right.shuffle(&mut rng);
+        let edges = (0..count).flat_map(|i| (0..k).map(move |j| (i, (i + j) % count)));
+        // Switching left -> right arcs as a digraph keeps every edge between the sides.
+        let mut switch = SwitchGraph::new(edges.map(|(i, j)| (left[i], right[j])), true);
+        for _ in 0..switch_steps {
+            switch.switch(false, false);
+        }
+
+        Graph::from_switch_graph(&switch, 2 * count, false, || {
+            if use_weight { Some(weight_gen(&mut rng)) } else { None }
+        })
+    }
+
+    /// Adjacency lists of a bipartite graph whose left side is
+    /// `1..=left_count`. Entry `i` of the first list holds the right
+    /// neighbours of left node `i + 1`, numbered `1..=right_count`; the second
+    /// list is the same from the right side.
+    pub fn to_bipartite_adj(&self, left_count: usize) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+        let right_count = self.node_count() - left_count;
+        let mut left_adj = vec![Vec::new(); left_count];
+        let mut right_adj = vec![Vec::new(); right_count];
+        for edge in self.iter_edges_all() {
+            if edge.u <= left_count && edge.v > left_count {
+                left_adj[edge.u - 1].push(edge.v - left_count);
+                right_adj[edge.v - left_count - 1].push(edge.u);
+            }
+        }
+        for list in left_adj.iter_mut().chain(right_adj.iter_mut()) {
+            list.sort_unstable();
+        }
+        (left_adj, right_adj)
+    }
+
+    /// Bipartite graph with the `planted` edges plus random edges that each
+    /// touch one of the two cover sets.
+    fn bipartite_covered(
+        left_count: usize,
+        right_count: usize,
+        edge_count: usize,
+        (cover_left, cover_right): (Vec<usize>, Vec<usize>),
+        planted: Vec<(usize, usize)>,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(left_count > 0 && right_count > 0, "partition sizes must be above zero");
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        // Allowed edges split into cover_left x right and rest_left x cover_right.
+        let in_cover: std::collections::HashSet<usize> = cover_left.iter().cloned().collect();
+        let rest_left: Vec<usize> = (1..=left_count).filter(|u| !in_cover.contains(u)).collect();
+        let first = cover_left.len() * right_count;
+        let second = rest_left.len() * cover_right.len();
+        assert!(
+            edge_count <= first + second,
+            "edge_count exceeds max possible edges for this configuration"
+        );
+
+        let mut graph = Graph::new(left_count + right_count, false);
+        let mut used: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+        for (u, v) in planted {
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(u, v, weight);
+            used.insert((u, v));
+        }
+
+        while used.len() < edge_count {
+            let (u, v) = if rng.random_range(0..first + second) < first {
+                (
+                    cover_left[rng.random_range(0..cover_left.len())],
+                    left_count + rng.random_range(1..=right_count),
+                )
+            } else {
+                (
+                    rest_left[rng.random_range(0..rest_left.len())],
+                    cover_right[rng.random_range(0..cover_right.len())],
+                )
+            };
+            if !used.insert((u, v)) {
+                continue;
+            }
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(u, v, weight);
+        }
+
+        graph
+    }
+
     pub fn k_regular_approx(
         point_count: usize,
         k: usize,
@@ -1540,6 +2827,97 @@ impl Graph {
         graph
     }
 
+    /// Random simple `k`-regular undirected graph.
+    ///
+    /// Starts from the circulant graph joining every node to its `k / 2`
+    /// nearest neighbours on each side (plus the opposite node when `k` is
+    /// odd) and randomizes it with `switch_steps` degree-preserving calls to
+    /// [`SwitchGraph::switch`]. A few switches per edge give a well mixed
+    /// graph. `point_count * k` must be even and `k < point_count`.
+    pub fn k_regular(
+        point_count: usize,
+        k: usize,
+        switch_steps: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(k < point_count, "k must be below point_count");
+        assert!((point_count * k).is_multiple_of(2), "point_count * k must be even");
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut edges = Vec::with_capacity(point_count * k / 2);
+        for i in 0..point_count {
+            for j in 1..=k / 2 {
+                edges.push((i + 1, (i + j) % point_count + 1));
+            }
+            if k % 2 == 1 && i < point_count / 2 {
+                edges.push((i + 1, i + point_count / 2 + 1));
+            }
+        }
+
+        let mut switch = SwitchGraph::new(edges, false);
+        for _ in 0..switch_steps {
+            switch.switch(false, false);
+        }
+
+        Graph::from_switch_graph(&switch, point_count, switch.directed, || {
+            if use_weight { Some(weight_gen(&mut rng)) } else { None }
+        })
+    }
+
+    /// Random simple directed graph where every node has in-degree and
+    /// out-degree exactly `k`, built like [`Graph::k_regular`] from the
+    /// circulant digraph `i -> i + 1, ..., i + k`. `k < point_count`.
+    pub fn k_regular_directed(
+        point_count: usize,
+        k: usize,
+        switch_steps: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(k < point_count, "k must be below point_count");
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let edges = (0..point_count).flat_map(|i| (1..=k).map(move |j| (i + 1, (i + j) % point_count + 1)));
+        let mut switch = SwitchGraph::new(edges, true);
+        for _ in 0..switch_steps {
+            switch.switch(false, false);
+        }
+
+        Graph::from_switch_graph(&switch, point_count, switch.directed, || {
+            if use_weight { Some(weight_gen(&mut rng)) } else { None }
+        })
+    }
+
+    /// Copy the edges of a [`SwitchGraph`] on nodes `1..=point_count` into a
+    /// new graph, taking each weight from `weight`.
+    fn from_switch_graph<F>(switch: &SwitchGraph, point_count: usize, directed: bool, mut weight: F) -> Graph
+    where
+        F: FnMut() -> Option<i64>,
+    {
+        let mut graph = Graph::new(point_count, directed);
+        for (u, v) in switch.iter_edges() {
+            if switch.directed || u <= v {
+                graph.add_edge(u, v, weight());
+            }
+        }
+        graph
+    }
+
     pub fn dag(
         point_count: usize,
         edge_count: usize,
@@ -1770,16 +3148,39 @@ impl Graph {
         }
     }
 
-    pub fn forest(
+    /// Connected graph with `edge_count` edges laid out as one random walk
+    /// through every node, so it has an Euler circuit when `closed` and an
+    /// Euler path between two distinct ends otherwise. The walk never stays
+    /// in place, but it may repeat an edge, so parallel edges can appear.
+    /// Returns the graph and the walk as a node sequence.
+    pub fn eulerian(
         point_count: usize,
-        tree_count: usize,
-        weight_limit: Option<(i64, i64)>,
+        edge_count: usize,
         directed: bool,
+        closed: bool,
+        weight_limit: Option<(i64, i64)>,
         weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
-    ) -> Graph {
-        assert!(point_count > 0, "point_count must be above zero");
-        assert!(tree_count >= 1 && tree_count <= point_count, "invalid tree_count");
-
+    ) -> (Graph, Vec<usize>) {
+        assert!(point_count >= 2, "point_count must be at least 2");
+        if closed {
+            assert!(
+                edge_count >= point_count,
+                "edge_count must be >= point_count for an Euler circuit"
+            );
+            assert!(
+                point_count > 2 || edge_count.is_multiple_of(2),
+                "an Euler circuit on two nodes needs an even edge_count"
+            );
+        } else {
+            assert!(
+                edge_count >= point_count - 1,
+                "edge_count must be >= point_count - 1 for an Euler path"
+            );
+            assert!(
+                point_count > 2 || edge_count % 2 == 1,
+                "an open Euler path on two nodes needs an odd edge_count"
+            );
+        }
         let mut rng = rng();
         let use_weight = weight_limit.is_some() || weight_gen.is_some();
         let default_weight_gen = |rng: &mut ThreadRng| {
@@ -1788,12 +3189,191 @@ impl Graph {
         };
         let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
 
-        let mut parent: Vec<usize> = (0..=point_count).collect();
-        fn find(parent: &mut [usize], x: usize) -> usize {
-            if parent[x] != x {
-                parent[x] = find(parent, parent[x]);
-            }
-            parent[x]
+        let mut walk: Vec<usize> = (1..=point_count).collect();
+        walk.shuffle(&mut rng);
+        let first = walk[0];
+        // A closed walk of `edge_count` edges has `edge_count` nodes before
+        // returning to the start; an open one has `edge_count + 1`.
+        let length = if closed { edge_count } else { edge_count + 1 };
+        while walk.len() < length {
+            let last = *walk.last().unwrap();
+            let is_final = walk.len() + 1 == length;
+            let next = loop {
+                let x = rng.random_range(1..=point_count);
+                if x != last && !(is_final && x == first) {
+                    break x;
+                }
+            };
+            walk.push(next);
+        }
+        if closed {
+            walk.push(first);
+        }
+
+        let mut graph = Graph::new(point_count, directed);
+        for pair in walk.windows(2) {
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(pair[0], pair[1], weight);
+        }
+        (graph, walk)
+    }
+
+    /// Simple graph with a planted Hamiltonian path (or cycle when `cycle`)
+    /// hidden among `edge_count` edges; the other edges are random. Returns
+    /// the graph and the planted node order.
+    pub fn hamiltonian(
+        point_count: usize,
+        edge_count: usize,
+        directed: bool,
+        cycle: bool,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> (Graph, Vec<usize>) {
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(!cycle || point_count >= 3, "a Hamiltonian cycle needs at least 3 nodes");
+        let planted = if cycle { point_count } else { point_count - 1 };
+        assert!(edge_count >= planted, "edge_count too small for the planted path");
+        assert!(
+            edge_count <= Graph::max_edge_count(point_count, directed, false),
+            "edge_count exceeds max possible edges"
+        );
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut order: Vec<usize> = (1..=point_count).collect();
+        order.shuffle(&mut rng);
+        let key = |u: usize, v: usize| if directed || u < v { (u, v) } else { (v, u) };
+
+        let mut graph = Graph::new(point_count, directed);
+        let mut used: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+        for i in 0..planted {
+            let (u, v) = (order[i], order[(i + 1) % point_count]);
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(u, v, weight);
+            used.insert(key(u, v));
+        }
+        while used.len() < edge_count {
+            let u = rng.random_range(1..=point_count);
+            let v = rng.random_range(1..=point_count);
+            if u == v || !used.insert(key(u, v)) {
+                continue;
+            }
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(u, v, weight);
+        }
+        (graph, order)
+    }
+
+    /// Connected simple graph guaranteed to have no Hamiltonian cycle (or,
+    /// when `path`, no Hamiltonian path). It is bipartite with one side
+    /// larger than the other by one (two for `path`), and any such cycle or
+    /// path would have to alternate between the sides.
+    pub fn non_hamiltonian(
+        point_count: usize,
+        edge_count: usize,
+        directed: bool,
+        path: bool,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        let gap = if path { 2 } else { 1 };
+        assert!(point_count >= gap + 2, "point_count too small");
+        let small = (point_count - gap) / 2;
+        let large = point_count - small;
+        let capacity = if directed { 2 * small * large } else { small * large };
+        assert!(
+            edge_count >= point_count - 1,
+            "edge_count must be >= point_count - 1 for connected graph"
+        );
+        assert!(edge_count <= capacity, "edge_count exceeds max possible edges");
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut labels: Vec<usize> = (1..=point_count).collect();
+        labels.shuffle(&mut rng);
+        let (left, right) = labels.split_at(large);
+        let key = |u: usize, v: usize| if directed || u < v { (u, v) } else { (v, u) };
+        let orient = |rng: &mut ThreadRng, u: usize, v: usize| {
+            if directed && rng.random_bool(0.5) { (v, u) } else { (u, v) }
+        };
+
+        let mut graph = Graph::new(point_count, directed);
+        let mut used: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+        // Spanning tree: every node hangs off an already placed node from the
+        // other side.
+        let mut placed = (vec![left[0]], vec![right[0]]);
+        let mut rest: Vec<(usize, bool)> = left[1..]
+            .iter()
+            .map(|&x| (x, true))
+            .chain(right[1..].iter().map(|&x| (x, false)))
+            .collect();
+        rest.shuffle(&mut rng);
+        let mut tree = vec![(left[0], right[0])];
+        for (x, is_left) in rest {
+            if is_left {
+                tree.push((x, placed.1[rng.random_range(0..placed.1.len())]));
+                placed.0.push(x);
+            } else {
+                tree.push((placed.0[rng.random_range(0..placed.0.len())], x));
+                placed.1.push(x);
+            }
+        }
+        for (a, b) in tree {
+            let (u, v) = orient(&mut rng, a, b);
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(u, v, weight);
+            used.insert(key(u, v));
+        }
+        while used.len() < edge_count {
+            let a = left[rng.random_range(0..large)];
+            let b = right[rng.random_range(0..small)];
+            let (u, v) = orient(&mut rng, a, b);
+            if !used.insert(key(u, v)) {
+                continue;
+            }
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(u, v, weight);
+        }
+        graph
+    }
+
+    /// Random forest of `tree_count` trees joined by random edges. Only the
+    /// graph is returned; `forest_with_roots` and `forest_with_shapes` also
+    /// give the roots, sizes, depths and parent array of every tree.
+    pub fn forest(
+        point_count: usize,
+        tree_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        directed: bool,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(tree_count >= 1 && tree_count <= point_count, "invalid tree_count");
+
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut parent: Vec<usize> = (0..=point_count).collect();
+        fn find(parent: &mut [usize], x: usize) -> usize {
+            if parent[x] != x {
+                parent[x] = find(parent, parent[x]);
+            }
+            parent[x]
         }
         fn union(parent: &mut [usize], a: usize, b: usize) {
             let ra = find(parent, a);
@@ -1825,6 +3405,8 @@ impl Graph {
         graph
     }
 
+    /// `Graph::forest` plus `repeat_times` copies of random tree edges. Use
+    /// `forest_with_roots` when the layout of the trees is needed.
     pub fn forest_with_repeats(
         point_count: usize,
         tree_count: usize,
@@ -1853,6 +3435,272 @@ impl Graph {
         graph
     }
 
+    /// Random forest of `tree_count` trees with random sizes and shuffled
+    /// labels, returned with its roots, sizes, depths and parent array.
+    /// Edges point from parent to child when `directed`.
+    pub fn forest_with_roots(
+        point_count: usize,
+        tree_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        directed: bool,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Forest {
+        let shapes = vec![TreeShape::default(); tree_count];
+        Graph::forest_with_shapes(point_count, &shapes, weight_limit, directed, weight_gen)
+    }
+
+    /// Like `forest_with_roots`, with one tree per entry of `shapes`, each
+    /// built by `Graph::tree` with that shape.
+    pub fn forest_with_shapes(
+        point_count: usize,
+        shapes: &[TreeShape],
+        weight_limit: Option<(i64, i64)>,
+        directed: bool,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Forest {
+        let tree_count = shapes.len();
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(tree_count >= 1 && tree_count <= point_count, "invalid tree_count");
+
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let sizes = Merger::random_sizes(point_count, tree_count, 1);
+        let mut labels: Vec<usize> = (1..=point_count).collect();
+        labels.shuffle(&mut rng);
+
+        let mut forest = Forest {
+            graph: Graph::new(point_count, directed),
+            parent: vec![0; point_count + 1],
+            roots: Vec::with_capacity(tree_count),
+            sizes: sizes.clone(),
+            depths: Vec::with_capacity(tree_count),
+        };
+        let mut offset = 0;
+        for (&size, shape) in sizes.iter().zip(shapes) {
+            let tree = Graph::tree(size, shape.chain, shape.flower, None, true, None, None);
+            let label = &labels[offset..offset + size];
+            // `Graph::tree` roots at 1 and gives every child a larger label
+            // than its parent, so depths fill in label order.
+            let mut depth = vec![0; size + 1];
+            let mut edges: Vec<(usize, usize)> = tree.iter_edges().map(|e| (e.u, e.v)).collect();
+            edges.sort_unstable_by_key(|&(_, child)| child);
+            for (p, c) in edges {
+                depth[c] = depth[p] + 1;
+                forest.parent[label[c - 1]] = label[p - 1];
+                let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+                forest.graph.add_edge(label[p - 1], label[c - 1], weight);
+            }
+            forest.roots.push(label[0]);
+            forest.depths.push(depth.into_iter().max().unwrap_or(0));
+            offset += size;
+        }
+        forest
+    }
+
+    /// Random connected cactus with `cycle_count` cycles whose lengths are
+    /// drawn from `cycle_len` (at least 3), the rest of the nodes hanging off
+    /// as tree edges. Cycles are shortened when `point_count` is too small to
+    /// fit the drawn lengths.
+    pub fn cactus(
+        point_count: usize,
+        cycle_count: usize,
+        cycle_len: (usize, usize),
+        kind: CactusKind,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+        shuffle: bool,
+    ) -> Graph {
+        let (min_len, max_len) = cycle_len;
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(min_len >= 3 && min_len <= max_len, "cycle_len must satisfy 3 <= min <= max");
+        // A vertex-cactus cycle is made of new nodes only, an edge-cactus cycle
+        // reuses the vertex it is attached to.
+        let reused = if kind == CactusKind::Edge { 1 } else { 0 };
+        assert!(
+            cycle_count * (min_len - reused) < point_count,
+            "point_count too small for the requested cycles"
+        );
+
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let budget = point_count - 1;
+        let mut lens: Vec<usize> = (0..cycle_count).map(|_| rng.random_range(min_len..=max_len)).collect();
+        let mut used: usize = lens.iter().map(|len| len - reused).sum();
+        while used > budget {
+            let i = rng.random_range(0..lens.len());
+            if lens[i] > min_len {
+                lens[i] -= 1;
+                used -= 1;
+            }
+        }
+
+        // Each block is either a cycle length or 0 for a single tree node.
+        let mut blocks = lens;
+        blocks.resize(cycle_count + budget - used, 0);
+        blocks.shuffle(&mut rng);
+
+        let mut graph = Graph::new(point_count, false);
+        let mut on_cycle = vec![false; point_count + 1];
+        let mut next = 2usize;
+        for len in blocks {
+            let v = rng.random_range(1..next);
+            if len == 0 {
+                let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+                graph.add_edge(v, next, weight);
+                next += 1;
+                continue;
+            }
+
+            let mut cycle: Vec<usize> = Vec::with_capacity(len);
+            if kind == CactusKind::Edge || (!on_cycle[v] && rng.random_bool(0.5)) {
+                cycle.push(v);
+            } else {
+                let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+                graph.add_edge(v, next, weight);
+            }
+            while cycle.len() < len {
+                cycle.push(next);
+                next += 1;
+            }
+            for i in 0..len {
+                on_cycle[cycle[i]] = true;
+                let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+                graph.add_edge(cycle[i], cycle[(i + 1) % len], weight);
+            }
+        }
+        // Vertex-cactus cycles attached directly to a vertex save one node.
+        while next <= point_count {
+            let v = rng.random_range(1..next);
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(v, next, weight);
+            next += 1;
+        }
+
+        if shuffle { graph.shuffle_labels() } else { graph }
+    }
+
+    /// Random connected pseudotree: a base cycle on `cycle_len` nodes with
+    /// random trees hanging off it, so it has exactly `point_count` edges.
+    pub fn pseudotree(
+        point_count: usize,
+        cycle_len: usize,
+        weight_limit: Option<(i64, i64)>,
+        directed: bool,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+        shuffle: bool,
+    ) -> Graph {
+        assert!(
+            cycle_len >= 3 && cycle_len <= point_count,
+            "cycle_len must be between 3 and point_count"
+        );
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut graph = Graph::new(point_count, directed);
+        for i in 1..=cycle_len {
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(i, i % cycle_len + 1, weight);
+        }
+        for i in (cycle_len + 1)..=point_count {
+            let father = rng.random_range(1..i);
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(father, i, weight);
+        }
+
+        if shuffle { graph.shuffle_labels() } else { graph }
+    }
+
+    /// Random functional graph, stored as directed edges `i -> f(i)`.
+    ///
+    /// The cycles have exactly the lengths in `cycle_lens` (a length of 1 is a
+    /// fixed point), and every other node is at most `max_tail_len` steps away
+    /// from a cycle. The longest tail has exactly `max_tail_len` nodes when
+    /// there are enough nodes left. Use [`Graph::to_functional`] to read `f`.
+    pub fn functional_graph(
+        point_count: usize,
+        cycle_lens: &[usize],
+        max_tail_len: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+        shuffle: bool,
+    ) -> Graph {
+        let cycle_total: usize = cycle_lens.iter().sum();
+        assert!(!cycle_lens.is_empty(), "need at least one cycle");
+        assert!(cycle_lens.iter().all(|&len| len > 0), "cycle lengths must be above zero");
+        assert!(cycle_total <= point_count, "cycles do not fit in point_count");
+        assert!(
+            cycle_total == point_count || max_tail_len > 0,
+            "max_tail_len must be above zero when there are tail nodes"
+        );
+
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut graph = Graph::new(point_count, true);
+        let mut start = 1usize;
+        for &len in cycle_lens {
+            for i in 0..len {
+                let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+                graph.add_edge(start + i, start + (i + 1) % len, weight);
+            }
+            start += len;
+        }
+
+        // depth[v] is the distance from v to its cycle; only nodes with
+        // depth < max_tail_len may receive new tail nodes.
+        let mut depth = vec![0usize; point_count + 1];
+        let mut parents: Vec<usize> = (1..start).collect();
+        for v in start..=point_count {
+            let father = if v - start < max_tail_len {
+                if v == start { rng.random_range(1..start) } else { v - 1 }
+            } else {
+                parents[rng.random_range(0..parents.len())]
+            };
+            depth[v] = depth[father] + 1;
+            if depth[v] < max_tail_len {
+                parents.push(v);
+            }
+            let weight = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(v, father, weight);
+        }
+
+        if shuffle { graph.shuffle_labels() } else { graph }
+    }
+
+    /// Read a graph with out-degree exactly one as a function: returns
+    /// `f(1), ..., f(n)` for nodes `1..=n`, or `None` if it is not one.
+    pub fn to_functional(&self) -> Option<Vec<usize>> {
+        let n = self.node_count();
+        (1..=n)
+            .map(|u| match self.edges.get(&u).map(|edges| edges.as_slice()) {
+                Some([edge]) => Some(edge.v),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn from_directed_degree_sequence(
         degree_sequence: &[(usize, usize)],
         self_loop: bool,
@@ -1887,17 +3735,88 @@ impl Graph {
         Ok(graph)
     }
 
+    /// Graph with the given degree sequence. With `options`, the graph is
+    /// then randomized by degree-preserving edge switches: enough of them (a
+    /// few per edge) make the result close to uniform among graphs with this
+    /// degree sequence; `keep_connected` never splits a component, so the
+    /// switches only ever merge the pieces of the initial construction.
     pub fn from_degree_sequence(
         degree_sequence: DegreeSequence<'_>,
         self_loop: bool,
         repeated_edges: bool,
+        options: Option<SwitchOptions>,
     ) -> Result<Graph, &'static str> {
-        match degree_sequence {
-            DegreeSequence::Directed(seq) => {
-                Graph::from_directed_degree_sequence(seq, self_loop, repeated_edges)
+        let Some(options) = options else {
+            return match degree_sequence {
+                DegreeSequence::Directed(seq) => {
+                    Graph::from_directed_degree_sequence(seq, self_loop, repeated_edges)
+                }
+                DegreeSequence::Undirected(seq) => {
+                    Graph::from_undirected_degree_sequence(seq, self_loop, repeated_edges)
+                }
+            };
+        };
+        let (mut switch, point_count) = match degree_sequence {
+            DegreeSequence::Directed(seq) => (
+                SwitchGraph::from_directed_degree_sequence(seq, self_loop, repeated_edges)?,
+                seq.len(),
+            ),
+            DegreeSequence::Undirected(seq) => (
+                SwitchGraph::from_undirected_degree_sequence(seq, self_loop, repeated_edges)?,
+                seq.len(),
+            ),
+        };
+        switch.mix(&options, self_loop, repeated_edges);
+        Ok(Graph::from_switch_graph(&switch, point_count, switch.directed, || None))
+    }
+
+    /// Random graphical degree sequence following a discrete power law
+    /// `P(d) ~ d^-exponent` on `min_degree..=max_degree`, as seen in real
+    /// networks. Feed it to [`Graph::from_degree_sequence`].
+    pub fn power_law_degree_sequence(
+        point_count: usize,
+        exponent: f64,
+        min_degree: usize,
+        max_degree: usize,
+    ) -> Vec<usize> {
+        assert!(
+            1 <= min_degree && min_degree <= max_degree && max_degree < point_count,
+            "degrees must satisfy 1 <= min_degree <= max_degree < point_count"
+        );
+        assert!(
+            min_degree < max_degree || (point_count * min_degree).is_multiple_of(2),
+            "no degree sequence with an even sum fits in the range"
+        );
+        let mut rng = rng();
+        let weights: Vec<f64> = (min_degree..=max_degree).map(|d| (d as f64).powf(-exponent)).collect();
+        let total: f64 = weights.iter().sum();
+
+        loop {
+            let mut degrees: Vec<usize> = (0..point_count)
+                .map(|_| {
+                    let mut target = rng.random::<f64>() * total;
+                    for (i, w) in weights.iter().enumerate() {
+                        if target < *w {
+                            return min_degree + i;
+                        }
+                        target -= w;
+                    }
+                    max_degree
+                })
+                .collect();
+
+            if !degrees.iter().sum::<usize>().is_multiple_of(2) {
+                let i = rng.random_range(0..point_count);
+                if degrees[i] < max_degree {
+                    degrees[i] += 1;
+                } else if degrees[i] > min_degree {
+                    degrees[i] -= 1;
+                } else {
+                    continue;
+                }
             }
-            DegreeSequence::Undirected(seq) => {
-                Graph::from_undirected_degree_sequence(seq, self_loop, repeated_edges)
+            if SwitchGraph::is_graphical(&degrees) {
+                return degrees;
             }
         }
     }
@@ -1988,17 +3907,871 @@ impl Graph {
 
         graph
     }
-}
 
-pub struct GraphMatrix<T> {
-    matrix: Vec<Vec<T>>,
-    default: T,
-}
+    /// Undirected `rows x cols` grid that makes plain (FIFO) SPFA run in
+    /// roughly `O(nm)`. Horizontal edges get random weights from
+    /// `weight_limit` (default `(1, 1_000_000)`), vertical edges get the
+    /// minimum weight. Nodes are numbered row by row, the source is node 1.
+    /// Keep the grid wide: few rows and many columns (e.g. `10 x n/10`);
+    /// a tall grid lets SPFA finish in a few passes.
+    pub fn hack_spfa_grid(rows: usize, cols: usize, weight_limit: Option<(i64, i64)>) -> Graph {
+        assert!(rows > 0 && cols > 0, "rows and cols must be above zero");
+        let mut rng = rng();
+        let (min_w, max_w) = weight_limit.unwrap_or((1, 1_000_000));
+        let id = |r: usize, c: usize| r * cols + c + 1;
+
+        let mut graph = Graph::new(rows * cols, false);
+        for r in 0..rows {
+            for c in 0..cols {
+                if c + 1 < cols {
+                    graph.add_edge(id(r, c), id(r, c + 1), Some(rng.random_range(min_w..=max_w)));
+                }
+                if r + 1 < rows {
+                    graph.add_edge(id(r, c), id(r + 1, c), Some(min_w));
+                }
+            }
+        }
 
-impl<T: Clone> GraphMatrix<T> {
-    pub fn new(n: usize, default: T) -> Self {
-        let matrix = vec![vec![default.clone(); n]; n];
-        Self { matrix, default }
+        graph
+    }
+
+    /// Targets SPFA with the LLL (Large Label Last) optimization.
+    ///
+    /// This is [`Graph::hack_spfa_grid`] plus one extra node hanging off the
+    /// source with a huge weight. That node is always larger than the queue
+    /// average, so LLL keeps rotating it to the back and it never leaves the
+    /// queue, which pins the average high and turns LLL into plain SPFA.
+    pub fn hack_spfa_lll(rows: usize, cols: usize, weight_limit: Option<(i64, i64)>) -> Graph {
+        let mut graph = Graph::hack_spfa_grid(rows, cols, weight_limit);
+        let (_, max_w) = weight_limit.unwrap_or((1, 1_000_000));
+        let ballast = rows * cols + 1;
+        let ballast_weight = max_w.max(1).saturating_mul(ballast as i64);
+
+        graph.add_edge(1, ballast, Some(ballast_weight));
+        graph
+    }
+
+    /// Targets SPFA with the SLF (Small Label First) optimization, and also
+    /// defeats FIFO and LLL queues.
+    ///
+    /// Nodes `1..=k` form a chain (source 1), each chain node has a negative
+    /// edge to a hub `k + 1` that improves the hub's distance a bit more than
+    /// the previous one, and the hub fans out to the remaining leaves. The hub
+    /// always has a smaller label than the next chain node, so every queue
+    /// discipline processes it once per chain node: `O(n^2)` relaxations in
+    /// total. The graph is directed and has no negative cycle.
+    pub fn hack_spfa_slf(point_count: usize) -> Graph {
+        assert!(point_count > 2, "point_count must be above two");
+        let chain_len = point_count / 2;
+        let hub = chain_len + 1;
+        let mut graph = Graph::new(point_count, true);
+
+        for i in 1..chain_len {
+            graph.add_edge(i, i + 1, Some(1));
+        }
+        for i in 1..=chain_len {
+            graph.add_edge(i, hub, Some(-2 * i as i64));
+        }
+        for leaf in (hub + 1)..=point_count {
+            graph.add_edge(hub, leaf, Some(1));
+        }
+
+        graph
+    }
+
+    /// Targets Dijkstra implementations that neither keep a visited flag nor
+    /// skip stale heap entries (`if d > dist[u] continue`).
+    ///
+    /// The source 1 reaches a hub through `k` middle nodes, each offering a
+    /// slightly shorter path than the last, so the hub is pushed `k` times.
+    /// Every stale copy rescans the hub's fan of leaves, costing `O(n^2)`.
+    /// All weights are positive, so correct implementations stay linear.
+    pub fn hack_dijkstra_no_visited(point_count: usize) -> Graph {
+        assert!(point_count > 3, "point_count must be above three");
+        let middle = (point_count - 2) / 2;
+        let hub = middle + 2;
+        let mut graph = Graph::new(point_count, true);
+
+        for i in 1..=middle {
+            graph.add_edge(1, i + 1, Some(i as i64));
+            graph.add_edge(i + 1, hub, Some(2 * (middle - i) as i64 + 1));
+        }
+        for leaf in (hub + 1)..=point_count {
+            graph.add_edge(hub, leaf, Some(1));
+        }
+
+        graph
+    }
+
+    /// Negative-edge graph that breaks Dijkstra.
+    ///
+    /// A chain of gadgets `a -> b` (weight 1), `a -> c -> b` (weights `X` and
+    /// `-X`) is followed by a fan of leaves. `X` is large enough that `b` and
+    /// everything after it is settled before `c`, so versions with a visited
+    /// flag report wrong distances, and versions that re-insert improved nodes
+    /// re-expand the whole tail once per gadget. The source is node 1 and
+    /// there is no negative cycle.
+    pub fn hack_dijkstra_negative(point_count: usize) -> Graph {
+        assert!(point_count > 2, "point_count must be above two");
+        let gadget_count = ((point_count - 1) / 4).max(1);
+        let tail = point_count - 1 - 2 * gadget_count;
+        let step = (gadget_count + tail + 2) as i64;
+        let mut graph = Graph::new(point_count, true);
+
+        let mut a = 1;
+        for i in 1..=gadget_count {
+            let c = 2 * i;
+            let b = 2 * i + 1;
+            let x = (gadget_count - i + 1) as i64 * step;
+            graph.add_edge(a, b, Some(1));
+            graph.add_edge(a, c, Some(x));
+            graph.add_edge(c, b, Some(-x));
+            a = b;
+        }
+        for leaf in (a + 1)..=point_count {
+            graph.add_edge(a, leaf, Some(1));
+        }
+
+        graph
+    }
+}
+
+impl Graph {
+    /// Random simple digraph guaranteed to contain a negative cycle.
+    ///
+    /// Random edges come from [`Graph::graph_with_weight_limit`], then a cycle
+    /// of `cycle_len` distinct nodes with total weight below zero is planted.
+    /// If `source` is given, the cycle is reachable from it. Returns the graph
+    /// and the cycle as a node sequence `v0 -> v1 -> ... -> v0`.
+    pub fn negative_cycle_graph(
+        point_count: usize,
+        edge_count: usize,
+        cycle_len: usize,
+        weight_limit: (i64, i64),
+        source: Option<usize>,
+    ) -> (Graph, Vec<usize>) {
+        let (min_w, max_w) = weight_limit;
+        assert!(min_w < 0, "weight_limit must allow negative weights");
+        let mut rng = rng();
+        let cycle = Graph::random_cycle_nodes(&mut rng, point_count, cycle_len);
+
+        let mut weights: Vec<i64> = (0..cycle_len).map(|_| rng.random_range(min_w..=max_w)).collect();
+        let mut need = weights.iter().sum::<i64>() + 1;
+        for w in weights.iter_mut() {
+            if need <= 0 {
+                break;
+            }
+            let cut = need.min(*w - min_w);
+            *w -= cut;
+            need -= cut;
+        }
+
+        let mut planted = Graph::cycle_edges(&cycle, &weights);
+        if let Some(source) = source {
+            assert!((1..=point_count).contains(&source), "source out of range");
+            if !cycle.contains(&source) {
+                let target = cycle[rng.random_range(0..cycle_len)];
+                planted.push((source, target, rng.random_range(min_w..=max_w)));
+            }
+        }
+
+        Graph::validate_planted(point_count, edge_count, planted.len());
+        let options = GraphGenOptions {
+            directed: true,
+            ..GraphGenOptions::default()
+        };
+        let mut graph = Graph::graph_with_weight_limit(
+            point_count,
+            edge_count - planted.len(),
+            options,
+            weight_limit,
+        );
+        graph.plant_edges(&planted, |rng, _, _| rng.random_range(min_w..=max_w));
+        (graph, cycle)
+    }
+
+    /// Random simple digraph guaranteed to have no negative cycle.
+    ///
+    /// Weights are `p(v) - p(u) + slack` for random node potentials `p` and a
+    /// non-negative slack, kept inside `weight_limit`, so every cycle has a
+    /// non-negative total while single edges may still be negative.
+    pub fn no_negative_cycle_graph(
+        point_count: usize,
+        edge_count: usize,
+        weight_limit: (i64, i64),
+    ) -> Graph {
+        let mut rng = rng();
+        let potentials = Graph::random_potentials(&mut rng, point_count, weight_limit);
+        let mut graph = Graph::graph(point_count, edge_count, true, false, false, None, None);
+        for edge in graph.iter_edges_all_mut() {
+            edge.w = Graph::potential_weight(&mut rng, &potentials, edge.u, edge.v, weight_limit);
+            edge.weighted = true;
+        }
+        graph
+    }
+
+    /// Like [`Graph::no_negative_cycle_graph`], but with a planted cycle of
+    /// `cycle_len` nodes whose total weight is exactly zero. Returns the graph
+    /// and the zero-weight cycle.
+    pub fn zero_cycle_graph(
+        point_count: usize,
+        edge_count: usize,
+        cycle_len: usize,
+        weight_limit: (i64, i64),
+    ) -> (Graph, Vec<usize>) {
+        assert!(
+            weight_limit.0 <= 0 && weight_limit.1 >= 0,
+            "weight_limit must contain zero"
+        );
+        let mut rng = rng();
+        let cycle = Graph::random_cycle_nodes(&mut rng, point_count, cycle_len);
+        let potentials = Graph::random_potentials(&mut rng, point_count, weight_limit);
+        Graph::validate_planted(point_count, edge_count, cycle_len);
+
+        let mut graph = Graph::graph(point_count, edge_count - cycle_len, true, false, false, None, None);
+        for edge in graph.iter_edges_all_mut() {
+            edge.w = Graph::potential_weight(&mut rng, &potentials, edge.u, edge.v, weight_limit);
+            edge.weighted = true;
+        }
+
+        // Zero slack on every cycle edge makes the potentials cancel out.
+        let weights: Vec<i64> = (0..cycle_len)
+            .map(|i| potentials[cycle[(i + 1) % cycle_len]] - potentials[cycle[i]])
+            .collect();
+        graph.plant_edges(&Graph::cycle_edges(&cycle, &weights), |rng, u, v| {
+            Graph::potential_weight(rng, &potentials, u, v, weight_limit)
+        });
+        (graph, cycle)
+    }
+
+    /// Connected simple graph whose minimum spanning tree is unique, even
+    /// though weights may repeat: every non-tree edge is strictly heavier
+    /// than the heaviest edge on the tree path it closes. Needs
+    /// `min < max` in `weight_limit`. Returns the graph and the MST weight.
+    pub fn unique_mst_graph(point_count: usize, edge_count: usize, weight_limit: (i64, i64)) -> (Graph, i64) {
+        let (min_w, max_w) = weight_limit;
+        assert!(min_w < max_w, "weight_limit needs at least two values");
+        Graph::planted_mst_graph(point_count, edge_count, (min_w, max_w - 1), |rng, path_max| {
+            rng.random_range(path_max + 1..=max_w)
+        })
+    }
+
+    /// Connected simple graph with many minimum spanning trees: every
+    /// non-tree edge weighs exactly as much as the heaviest edge on the tree
+    /// path it closes, so it can replace that edge. Returns the graph and the
+    /// MST weight.
+    pub fn tied_mst_graph(point_count: usize, edge_count: usize, weight_limit: (i64, i64)) -> (Graph, i64) {
+        let (min_w, max_w) = weight_limit;
+        assert!(min_w <= max_w, "weight_limit must satisfy min <= max");
+        Graph::planted_mst_graph(point_count, edge_count, weight_limit, |_, path_max| path_max)
+    }
+
+    /// Random tree with weights from `tree_limit`, plus random extra edges
+    /// weighted by `extra_weight(rng, max weight on the tree path)`.
+    fn planted_mst_graph<F>(
+        point_count: usize,
+        edge_count: usize,
+        tree_limit: (i64, i64),
+        mut extra_weight: F,
+    ) -> (Graph, i64)
+    where
+        F: FnMut(&mut ThreadRng, i64) -> i64,
+    {
+        assert!(point_count > 0, "point_count must be above zero");
+        assert!(edge_count + 1 >= point_count, "edge_count too small to connect the graph");
+        Graph::validate_graph_params(point_count, edge_count, false, false, false);
+
+        let mut rng = rng();
+        let mut order: Vec<usize> = (1..=point_count).collect();
+        order.shuffle(&mut rng);
+
+        // Binary lifting over the tree: up[k][v] is the 2^k-th ancestor of v
+        // and top[k][v] the heaviest edge on the way there.
+        let levels = usize::BITS as usize - point_count.leading_zeros() as usize;
+        let mut up = vec![vec![0; point_count + 1]; levels.max(1)];
+        let mut top = vec![vec![i64::MIN; point_count + 1]; levels.max(1)];
+        let mut depth = vec![0; point_count + 1];
+        up[0][order[0]] = order[0];
+
+        let mut graph = Graph::new(point_count, false);
+        let mut used = std::collections::HashSet::with_capacity(edge_count);
+        let mut total = 0;
+        for i in 1..point_count {
+            let v = order[i];
+            let parent = order[rng.random_range(0..i)];
+            let w = rng.random_range(tree_limit.0..=tree_limit.1);
+            graph.add_edge(parent, v, Some(w));
+            used.insert((parent.min(v), parent.max(v)));
+            total += w;
+            up[0][v] = parent;
+            top[0][v] = w;
+            depth[v] = depth[parent] + 1;
+        }
+        for k in 1..up.len() {
+            for &v in &order {
+                let mid = up[k - 1][v];
+                up[k][v] = up[k - 1][mid];
+                top[k][v] = top[k - 1][v].max(top[k - 1][mid]);
+            }
+        }
+
+        let path_max = |mut u: usize, mut v: usize| {
+            let mut best = i64::MIN;
+            if depth[u] < depth[v] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            for k in (0..up.len()).rev() {
+                if depth[u] - depth[v] >= 1 << k {
+                    best = best.max(top[k][u]);
+                    u = up[k][u];
+                }
+            }
+            if u == v {
+                return best;
+            }
+            for k in (0..up.len()).rev() {
+                if up[k][u] != up[k][v] {
+                    best = best.max(top[k][u]).max(top[k][v]);
+                    u = up[k][u];
+                    v = up[k][v];
+                }
+            }
+            best.max(top[0][u]).max(top[0][v])
+        };
+
+        let mut count = point_count - 1;
+        while count < edge_count {
+            let u = rng.random_range(1..=point_count);
+            let v = rng.random_range(1..=point_count);
+            if u == v || !used.insert((u.min(v), u.max(v))) {
+                continue;
+            }
+            let w = extra_weight(&mut rng, path_max(u, v));
+            graph.add_edge(u, v, Some(w));
+            count += 1;
+        }
+        (graph, total)
+    }
+
+    fn random_cycle_nodes(rng: &mut ThreadRng, point_count: usize, cycle_len: usize) -> Vec<usize> {
+        assert!(
+            cycle_len >= 2 && cycle_len <= point_count,
+            "cycle_len must be between 2 and point_count"
+        );
+        let mut nodes: Vec<usize> = (1..=point_count).collect();
+        nodes.shuffle(rng);
+        nodes.truncate(cycle_len);
+        nodes
+    }
+
+    fn cycle_edges(cycle: &[usize], weights: &[i64]) -> Vec<(usize, usize, i64)> {
+        (0..cycle.len())
+            .map(|i| (cycle[i], cycle[(i + 1) % cycle.len()], weights[i]))
+            .collect()
+    }
+
+    fn validate_planted(point_count: usize, edge_count: usize, planted: usize) {
+        assert!(edge_count >= planted, "edge_count too small for the planted edges");
+        Graph::validate_graph_params(point_count, edge_count, true, false, false);
+    }
+
+    /// Potentials are bounded so that `p(v) - p(u)` always fits in `weight_limit`.
+    fn random_potentials(rng: &mut ThreadRng, point_count: usize, weight_limit: (i64, i64)) -> Vec<i64> {
+        let (min_w, max_w) = weight_limit;
+        assert!(max_w >= 0 && min_w <= max_w, "weight_limit must allow non-negative weights");
+        let span = min_w.saturating_neg().min(max_w).max(0);
+        (0..=point_count).map(|_| rng.random_range(0..=span)).collect()
+    }
+
+    fn potential_weight(
+        rng: &mut ThreadRng,
+        potentials: &[i64],
+        u: usize,
+        v: usize,
+        weight_limit: (i64, i64),
+    ) -> i64 {
+        let low = (potentials[v] - potentials[u]).max(weight_limit.0);
+        rng.random_range(low..=weight_limit.1)
+    }
+
+    /// Insert the planted directed edges into a simple digraph. A planted edge
+    /// that already exists only has its weight replaced, and a random edge
+    /// from `weight_gen` is added instead so the edge count is preserved.
+    fn plant_edges<F>(&mut self, planted: &[(usize, usize, i64)], mut weight_gen: F)
+    where
+        F: FnMut(&mut ThreadRng, usize, usize) -> i64,
+    {
+        let mut rng = rng();
+        let point_count = self.node_count();
+        let mut used: std::collections::HashSet<(usize, usize)> =
+            self.iter_edges_all().map(|e| (e.u, e.v)).collect();
+        let mut missing = 0usize;
+
+        for &(u, v, w) in planted {
+            if used.insert((u, v)) {
+                self.add_edge(u, v, Some(w));
+            } else {
+                for edge in self.edges.get_mut(&u).into_iter().flatten() {
+                    if edge.v == v {
+                        edge.w = w;
+                        edge.weighted = true;
+                    }
+                }
+                missing += 1;
+            }
+        }
+
+        while missing > 0 {
+            let u = rng.random_range(1..=point_count);
+            let v = rng.random_range(1..=point_count);
+            if u == v || !used.insert((u, v)) {
+                continue;
+            }
+            let w = weight_gen(&mut rng, u, v);
+            self.add_edge(u, v, Some(w));
+            missing -= 1;
+        }
+    }
+}
+
+/// Largest coordinate magnitude accepted by the planar generators, so the
+/// exact in-circle test fits in 128-bit arithmetic.
+#[cfg(feature = "polygon")]
+const PLANAR_COORD_LIMIT: i64 = 1_000_000_000;
+
+#[cfg(feature = "polygon")]
+fn orient(a: Point, b: Point, c: Point) -> i128 {
+    (b.x - a.x) as i128 * (c.y - a.y) as i128 - (b.y - a.y) as i128 * (c.x - a.x) as i128
+}
+
+/// Whether `d` lies strictly inside the circumcircle of the counter-clockwise
+/// triangle `a`, `b`, `c`.
+#[cfg(feature = "polygon")]
+fn in_circle(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let lift = |p: Point| {
+        let (dx, dy) = ((p.x - d.x) as i128, (p.y - d.y) as i128);
+        (dx, dy, dx * dx + dy * dy)
+    };
+    let (adx, ady, al) = lift(a);
+    let (bdx, bdy, bl) = lift(b);
+    let (cdx, cdy, cl) = lift(c);
+    let terms = [
+        al * (bdx * cdy - cdx * bdy),
+        bl * (cdx * ady - adx * cdy),
+        cl * (adx * bdy - bdx * ady),
+    ];
+    // Each term fits in an i128 but their sum may not.
+    let (mut pos, mut neg) = (0u128, 0u128);
+    for t in terms {
+        if t >= 0 {
+            pos += t as u128;
+        } else {
+            neg += t.unsigned_abs();
+        }
+    }
+    pos > neg
+}
+
+/// Triangulation of a point set as counter-clockwise triangles stored three
+/// half-edges at a time: half-edge `e` runs from `triangles[e]` to the next
+/// corner of its triangle, and `twins[e]` is the opposite half-edge, or
+/// `NO_EDGE` on the hull.
+#[cfg(feature = "polygon")]
+struct Triangulation {
+    points: Vec<Point>,
+    order: Vec<usize>,
+    triangles: Vec<usize>,
+    twins: Vec<usize>,
+    hull_edge: Vec<usize>,
+}
+
+#[cfg(feature = "polygon")]
+const NO_EDGE: usize = usize::MAX;
+
+#[cfg(feature = "polygon")]
+fn next_half_edge(e: usize) -> usize {
+    if e % 3 == 2 { e - 2 } else { e + 1 }
+}
+
+#[cfg(feature = "polygon")]
+fn prev_half_edge(e: usize) -> usize {
+    if e.is_multiple_of(3) { e + 2 } else { e - 1 }
+}
+
+#[cfg(feature = "polygon")]
+impl Triangulation {
+    /// Delaunay triangulation of distinct points by a sweep: add them left to
+    /// right, connect each one to the hull edges it can see and flip those
+    /// edges until they are locally Delaunay again.
+    fn delaunay(points: Vec<Point>) -> Triangulation {
+        let n = points.len();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&i| (points[i].x, points[i].y));
+        let mut tri = Triangulation {
+            points,
+            order,
+            triangles: Vec::new(),
+            twins: Vec::new(),
+            hull_edge: vec![NO_EDGE; n],
+        };
+        if n < 3 {
+            return tri;
+        }
+
+        let o = tri.order.clone();
+        let pts = tri.points.clone();
+        let Some(j) = (2..n).find(|&j| orient(pts[o[0]], pts[o[1]], pts[o[j]]) != 0) else {
+            return tri;
+        };
+
+        // The first j points are collinear; fan them out to the point o[j].
+        let ccw = orient(pts[o[0]], pts[o[1]], pts[o[j]]) > 0;
+        let mut last_t = NO_EDGE;
+        for i in 0..j - 1 {
+            if ccw {
+                let t = tri.push(o[i], o[i + 1], o[j]);
+                tri.link(t + 2, if i > 0 { last_t + 1 } else { NO_EDGE });
+                tri.hull_edge[o[i]] = t;
+                last_t = t;
+            } else {
+                let t = tri.push(o[i + 1], o[i], o[j]);
+                tri.link(t + 1, if i > 0 { last_t + 2 } else { NO_EDGE });
+                tri.hull_edge[o[i + 1]] = t;
+                last_t = t;
+            }
+        }
+        let hull: Vec<usize> = if ccw {
+            tri.hull_edge[o[j - 1]] = last_t + 1;
+            tri.hull_edge[o[j]] = 2;
+            o[..=j].to_vec()
+        } else {
+            tri.hull_edge[o[0]] = 1;
+            tri.hull_edge[o[j]] = last_t + 2;
+            std::iter::once(o[0]).chain(o[1..=j].iter().rev().cloned()).collect()
+        };
+
+        let mut next = vec![usize::MAX; n];
+        let mut prev = vec![usize::MAX; n];
+        for (k, &v) in hull.iter().enumerate() {
+            let w = hull[(k + 1) % hull.len()];
+            next[v] = w;
+            prev[w] = v;
+        }
+
+        let mut last = o[j];
+        for &v in &o[j + 1..] {
+            let visible = |a: usize, b: usize| orient(pts[a], pts[b], pts[v]) < 0;
+            // The newest hull vertex is normally next to the visible chain;
+            // fall back to a full walk around the hull when it is not.
+            let mut seed = last;
+            if !visible(seed, next[seed]) && !visible(prev[seed], seed) {
+                while !visible(seed, next[seed]) {
+                    seed = next[seed];
+                }
+            }
+            let mut lo = seed;
+            while visible(prev[lo], lo) {
+                lo = prev[lo];
+            }
+            let mut hi = seed;
+            while visible(hi, next[hi]) {
+                hi = next[hi];
+            }
+
+            let mut outer = Vec::new();
+            let mut last_t = NO_EDGE;
+            let mut a = lo;
+            while a != hi {
+                let b = next[a];
+                let t = tri.push(a, v, b);
+                tri.link(t + 2, tri.hull_edge[a]);
+                if a == lo {
+                    tri.hull_edge[lo] = t;
+                } else {
+                    tri.link(t, last_t + 1);
+                }
+                outer.push(t + 2);
+                last_t = t;
+                a = b;
+            }
+            tri.hull_edge[v] = last_t + 1;
+            next[lo] = v;
+            prev[v] = lo;
+            next[v] = hi;
+            prev[hi] = v;
+            tri.legalize(outer);
+            last = v;
+        }
+        tri
+    }
+
+    fn push(&mut self, a: usize, b: usize, c: usize) -> usize {
+        self.triangles.extend([a, b, c]);
+        self.twins.extend([NO_EDGE; 3]);
+        self.triangles.len() - 3
+    }
+
+    fn link(&mut self, e: usize, f: usize) {
+        self.twins[e] = f;
+        if f != NO_EDGE {
+            self.twins[f] = e;
+        }
+    }
+
+    /// Replace the edge of half-edge `e` by the other diagonal of its two
+    /// triangles, if that quadrilateral is strictly convex.
+    fn flip(&mut self, e: usize) -> bool {
+        let f = self.twins[e];
+        if f == NO_EDGE {
+            return false;
+        }
+        let (u, v) = (self.triangles[e], self.triangles[f]);
+        let w = self.triangles[prev_half_edge(e)];
+        let x = self.triangles[prev_half_edge(f)];
+        let p = |i: usize| self.points[i];
+        if orient(p(u), p(x), p(w)) <= 0 || orient(p(x), p(v), p(w)) <= 0 {
+            return false;
+        }
+
+        let outer = [
+            self.twins[next_half_edge(f)],
+            self.twins[prev_half_edge(e)],
+            self.twins[prev_half_edge(f)],
+            self.twins[next_half_edge(e)],
+        ];
+        let (a, b) = (e - e % 3, f - f % 3);
+        self.triangles[a..a + 3].copy_from_slice(&[u, x, w]);
+        self.triangles[b..b + 3].copy_from_slice(&[x, v, w]);
+        for (slot, twin) in [a, a + 2, b, b + 1].into_iter().zip(outer) {
+            self.link(slot, twin);
+            if twin == NO_EDGE {
+                self.hull_edge[self.triangles[slot]] = slot;
+            }
+        }
+        self.link(a + 1, b + 2);
+        true
+    }
+
+    /// Lawson flips, starting from the half-edges on `stack`, until none of
+    /// the touched edges has a point inside a neighbouring circumcircle.
+    fn legalize(&mut self, mut stack: Vec<usize>) {
+        while let Some(e) = stack.pop() {
+            let f = self.twins[e];
+            if f == NO_EDGE {
+                continue;
+            }
+            let corner = |h: usize| self.points[self.triangles[h]];
+            if in_circle(corner(e), corner(f), corner(prev_half_edge(e)), corner(prev_half_edge(f)))
+                && self.flip(e)
+            {
+                let (a, b) = (e - e % 3, f - f % 3);
+                stack.extend([a, a + 2, b, b + 1]);
+            }
+        }
+    }
+
+    /// Every edge once. Without triangles all points are collinear and the
+    /// triangulation is the path through them.
+    fn edges(&self) -> Vec<(usize, usize)> {
+        if self.triangles.is_empty() {
+            return self.order.windows(2).map(|w| (w[0], w[1])).collect();
+        }
+        (0..self.triangles.len())
+            .filter(|&e| self.twins[e] == NO_EDGE || e < self.twins[e])
+            .map(|e| (self.triangles[e], self.triangles[next_half_edge(e)]))
+            .collect()
+    }
+}
+
+#[cfg(feature = "polygon")]
+impl Graph {
+    /// Delaunay triangulation of distinct `points`, with node `i + 1` at
+    /// `points[i]`. Collinear points give a path. Coordinates must stay within
+    /// `±10^9`.
+    pub fn delaunay(
+        points: &[Point],
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        assert!(
+            points.iter().all(|p| p.x.abs() <= PLANAR_COORD_LIMIT && p.y.abs() <= PLANAR_COORD_LIMIT),
+            "coordinates must be within 1e9 in absolute value"
+        );
+        let distinct: std::collections::HashSet<Point> = points.iter().cloned().collect();
+        assert!(distinct.len() == points.len(), "points must be distinct");
+
+        let tri = Triangulation::delaunay(points.to_vec());
+        let labels: Vec<usize> = (1..=points.len()).collect();
+        Graph::from_plane_edges(&labels, &tri.edges(), weight_limit, weight_gen)
+    }
+
+    /// Delaunay triangulation of `point_count` distinct random points from
+    /// [`random_points`]. Returns the graph and the coordinates of node
+    /// `i + 1` at index `i`.
+    pub fn random_triangulation(
+        point_count: usize,
+        x_range: (i64, i64),
+        y_range: (i64, i64),
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> (Graph, Vec<Point>) {
+        let area = (x_range.1 - x_range.0 + 1) as u128 * (y_range.1 - y_range.0 + 1) as u128;
+        assert!(point_count as u128 <= area, "not enough lattice points in the ranges");
+
+        let mut seen: std::collections::HashSet<Point> = std::collections::HashSet::new();
+        let mut points: Vec<Point> = Vec::with_capacity(point_count);
+        while points.len() < point_count {
+            for p in random_points(point_count - points.len(), x_range, y_range) {
+                if seen.insert(p) {
+                    points.push(p);
+                }
+            }
+        }
+        (Graph::delaunay(&points, weight_limit, weight_gen), points)
+    }
+
+    /// Random maximal planar graph (`3n - 6` edges, every face a triangle)
+    /// with a straight-line embedding. Points are drawn inside the triangle
+    /// `(0, 0)`, `(coord_limit, 0)`, `(0, coord_limit)`, whose corners are
+    /// nodes 1 to 3 before shuffling, triangulated, and then `flip_count`
+    /// random edge flips mix the triangulation. Returns the graph and the
+    /// coordinates of node `i + 1` at index `i`.
+    pub fn maximal_planar(
+        point_count: usize,
+        coord_limit: i64,
+        flip_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+        shuffle: bool,
+    ) -> (Graph, Vec<Point>) {
+        assert!(point_count >= 3, "point_count must be at least 3");
+        assert!(
+            (3..=PLANAR_COORD_LIMIT).contains(&coord_limit),
+            "coord_limit must be between 3 and 1e9"
+        );
+        let interior = (coord_limit - 1) as u128 * (coord_limit - 2) as u128 / 2;
+        assert!((point_count - 3) as u128 <= interior, "coord_limit too small for point_count");
+
+        let mut rng = rng();
+        let mut points = vec![Point::new(0, 0), Point::new(coord_limit, 0), Point::new(0, coord_limit)];
+        let mut seen: std::collections::HashSet<Point> = points.iter().cloned().collect();
+        while points.len() < point_count {
+            let p = Point::new(rng.random_range(1..coord_limit), rng.random_range(1..coord_limit));
+            if p.x + p.y < coord_limit && seen.insert(p) {
+                points.push(p);
+            }
+        }
+
+        let mut tri = Triangulation::delaunay(points);
+        for _ in 0..flip_count {
+            let e = rng.random_range(0..tri.triangles.len());
+            tri.flip(e);
+        }
+        let edges = tri.edges();
+
+        let mut labels: Vec<usize> = (1..=point_count).collect();
+        if shuffle {
+            labels.shuffle(&mut rng);
+        }
+        let graph = Graph::from_plane_edges(&labels, &edges, weight_limit, weight_gen);
+        (graph, Graph::relabel_points(&labels, &tri.points))
+    }
+
+    /// Random connected outerplanar graph with `edge_count` edges, between
+    /// `point_count - 1` (a path) and `2 * point_count - 3` (a triangulated
+    /// polygon). Nodes sit in convex position on the parabola `y = x^2`, and
+    /// every edge is a side or a diagonal of that polygon. Returns the graph
+    /// and the coordinates of node `i + 1` at index `i`.
+    pub fn outerplanar(
+        point_count: usize,
+        edge_count: usize,
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+        shuffle: bool,
+    ) -> (Graph, Vec<Point>) {
+        assert!(point_count > 0, "point_count must be above zero");
+        let max_edges = if point_count < 3 { point_count - 1 } else { 2 * point_count - 3 };
+        assert!(
+            edge_count >= point_count - 1 && edge_count <= max_edges,
+            "edge_count must be between point_count - 1 and 2 * point_count - 3"
+        );
+        assert!(point_count as i64 <= 31_623, "point_count too large for parabola coordinates");
+
+        let mut rng = rng();
+        let mut edges: Vec<(usize, usize)> = (1..point_count).map(|i| (i - 1, i)).collect();
+        let mut extra: Vec<(usize, usize)> = Vec::new();
+        if point_count >= 3 {
+            extra.push((0, point_count - 1));
+            // Random triangulation of the polygon by splitting on a random apex.
+            let mut stack = vec![(0, point_count - 1)];
+            while let Some((i, j)) = stack.pop() {
+                if j - i < 2 {
+                    continue;
+                }
+                let k = rng.random_range(i + 1..j);
+                for (a, b) in [(i, k), (k, j)] {
+                    if b - a > 1 {
+                        extra.push((a, b));
+                    }
+                    stack.push((a, b));
+                }
+            }
+        }
+        extra.shuffle(&mut rng);
+        edges.extend(extra.into_iter().take(edge_count + 1 - point_count));
+
+        let points: Vec<Point> = (0..point_count as i64).map(|i| Point::new(i, i * i)).collect();
+        let mut labels: Vec<usize> = (1..=point_count).collect();
+        if shuffle {
+            labels.shuffle(&mut rng);
+        }
+        let graph = Graph::from_plane_edges(&labels, &edges, weight_limit, weight_gen);
+        (graph, Graph::relabel_points(&labels, &points))
+    }
+
+    /// Undirected graph on `labels` with an edge `labels[u] - labels[v]` for
+    /// each `(u, v)` in `edges`.
+    fn from_plane_edges(
+        labels: &[usize],
+        edges: &[(usize, usize)],
+        weight_limit: Option<(i64, i64)>,
+        weight_gen: Option<Box<dyn FnMut(&mut ThreadRng) -> i64>>,
+    ) -> Graph {
+        let mut rng = rng();
+        let use_weight = weight_limit.is_some() || weight_gen.is_some();
+        let default_weight_gen = |rng: &mut ThreadRng| {
+            let (min_weight, max_weight) = weight_limit.expect("weight_limit required for default generator");
+            rng.random_range(min_weight..=max_weight)
+        };
+        let mut weight_gen = weight_gen.unwrap_or_else(|| Box::new(default_weight_gen));
+
+        let mut graph = Graph::new(labels.len(), false);
+        for &(u, v) in edges {
+            let w = if use_weight { Some(weight_gen(&mut rng)) } else { None };
+            graph.add_edge(labels[u], labels[v], w);
+        }
+        graph
+    }
+
+    /// Reorder `points` so that index `labels[i] - 1` holds `points[i]`.
+    fn relabel_points(labels: &[usize], points: &[Point]) -> Vec<Point> {
+        let mut placed = points.to_vec();
+        for (i, &label) in labels.iter().enumerate() {
+            placed[label - 1] = points[i];
+        }
+        placed
+    }
+}
+
+pub struct GraphMatrix<T> {
+    matrix: Vec<Vec<T>>,
+    default: T,
+}
+
+impl<T: Clone> GraphMatrix<T> {
+    pub fn new(n: usize, default: T) -> Self {
+        let matrix = vec![vec![default.clone(); n]; n];
+        Self { matrix, default }
     }
 
     pub fn size(&self) -> usize {
@@ -2024,6 +4797,151 @@ impl<T: Clone> GraphMatrix<T> {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
         self.matrix.iter_mut().map(|row| row.as_mut_slice())
     }
+
+    /// Fill every off-diagonal cell with `f(u, v)`. With `symmetric`, only
+    /// `u < v` is generated and mirrored to `(v, u)`.
+    pub fn fill<F>(&mut self, symmetric: bool, mut f: F)
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let n = self.size();
+        for u in 0..n {
+            for v in 0..n {
+                if u == v || (symmetric && u > v) {
+                    continue;
+                }
+                let value = f(u, v);
+                if symmetric {
+                    self.matrix[v][u] = value.clone();
+                }
+                self.matrix[u][v] = value;
+            }
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool
+    where
+        T: PartialEq,
+    {
+        let n = self.size();
+        (0..n).all(|u| (0..u).all(|v| self.matrix[u][v] == self.matrix[v][u]))
+    }
+
+    /// Rows joined by newlines. Cells equal to the default value print as
+    /// `sentinel` when given (e.g. `"INF"` or `"-1"`); with `align`, every
+    /// column is right-aligned to its widest entry.
+    pub fn format(&self, sentinel: Option<&str>, align: bool) -> String
+    where
+        T: std::fmt::Display + PartialEq,
+    {
+        let cells: Vec<Vec<String>> = self
+            .matrix
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|item| match sentinel {
+                        Some(text) if *item == self.default => text.to_string(),
+                        _ => item.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut widths = vec![0; self.size()];
+        if align {
+            for row in &cells {
+                for (j, cell) in row.iter().enumerate() {
+                    widths[j] = widths[j].max(cell.len());
+                }
+            }
+        }
+        let lines: Vec<String> = cells
+            .iter()
+            .map(|row| {
+                let padded: Vec<String> = row
+                    .iter()
+                    .enumerate()
+                    .map(|(j, cell)| format!("{:>width$}", cell, width = widths[j]))
+                    .collect();
+                padded.join(" ")
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+impl GraphMatrix<i64> {
+    /// Adjacency matrix of `graph`, with node `i + 1` at index `i`. Missing
+    /// edges hold `no_edge`, parallel edges keep the smallest weight and
+    /// unweighted edges count as 1.
+    pub fn from_graph(graph: &Graph, no_edge: i64) -> GraphMatrix<i64> {
+        let n = graph
+            .edges
+            .keys()
+            .cloned()
+            .chain(graph.iter_edges_all().map(|e| e.v))
+            .max()
+            .unwrap_or(0);
+        let mut matrix = GraphMatrix::new(n, no_edge);
+        for edge in graph.iter_edges_all() {
+            let w = edge.weight().unwrap_or(1);
+            let cell = &mut matrix[(edge.u - 1, edge.v - 1)];
+            if *cell == no_edge || w < *cell {
+                *cell = w;
+            }
+        }
+        matrix
+    }
+
+    /// Graph on nodes `1..=size` with an edge for every off-diagonal cell
+    /// that differs from the default value. An undirected graph reads the
+    /// upper triangle only.
+    pub fn to_graph(&self, directed: bool) -> Graph {
+        let n = self.size();
+        let mut graph = Graph::new(n, directed);
+        for u in 0..n {
+            let start = if directed { 0 } else { u + 1 };
+            for v in start..n {
+                if u != v && self.matrix[u][v] != self.default {
+                    graph.add_edge(u + 1, v + 1, Some(self.matrix[u][v]));
+                }
+            }
+        }
+        graph
+    }
+
+    /// Random dense weighted graph as a distance matrix, as read by
+    /// Floyd-Warshall: each off-diagonal cell holds an edge with probability
+    /// `density`, missing edges hold `infinity` and the diagonal is 0.
+    /// `infinity` must lie outside `weight_limit` (usually above it).
+    pub fn random(
+        point_count: usize,
+        density: f64,
+        symmetric: bool,
+        infinity: i64,
+        weight_limit: (i64, i64),
+    ) -> GraphMatrix<i64> {
+        assert!((0.0..=1.0).contains(&density), "density must be between 0 and 1");
+        let (min_w, max_w) = weight_limit;
+        assert!(min_w <= max_w, "weight_limit must satisfy min <= max");
+        assert!(
+            !(min_w..=max_w).contains(&infinity),
+            "infinity must lie outside weight_limit, or edges could look missing"
+        );
+        let mut rng = rng();
+        let mut matrix = GraphMatrix::new(point_count, infinity);
+        matrix.fill(symmetric, |_, _| {
+            if rng.random_bool(density) {
+                rng.random_range(min_w..=max_w)
+            } else {
+                infinity
+            }
+        });
+        for i in 0..point_count {
+            matrix[(i, i)] = 0;
+        }
+        matrix
+    }
 }
 
 impl<T> std::ops::Index<(usize, usize)> for GraphMatrix<T> {
@@ -2040,23 +4958,146 @@ impl<T> std::ops::IndexMut<(usize, usize)> for GraphMatrix<T> {
     }
 }
 
+/// Joins graphs side by side. Component `i` is relabelled to `1..=n_i`,
+/// keeping the order of its labels, then shifted by the node counts of the
+/// components before it.
 pub struct Merger {
     directed: bool,
     graphs: Vec<Graph>,
+    links: Vec<(usize, usize, Option<i64>)>,
+    shared: Vec<(usize, usize)>,
+    shuffle: bool,
+}
+
+/// How `Merger::connect` joins two neighbouring components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MergeLink {
+    Shared,
+    Bridge,
+    Double,
 }
 
 impl Merger {
     pub fn new<I: IntoIterator<Item = Graph>>(graphs: I, directed: bool) -> Self {
         Self {
             directed,
-            graphs: graphs.into_iter().collect(),
+            graphs: graphs.into_iter().map(Self::compact).collect(),
+            links: Vec::new(),
+            shared: Vec::new(),
+            shuffle: false,
+        }
+    }
+
+    /// Shuffle the labels of the merged graph.
+    pub fn shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn add_graph(&mut self, graph: Graph) {
+        self.graphs.push(Self::compact(graph));
+    }
+
+    /// Relabel `graph` to `1..=n` in increasing label order, so graphs from
+    /// `subgraph_by_nodes`, `offset_labels` and the like can be merged.
+    fn compact(graph: Graph) -> Graph {
+        let mut nodes: Vec<usize> = graph.edges.keys().cloned().collect();
+        nodes.sort_unstable();
+        if nodes.iter().enumerate().all(|(i, &node)| node == i + 1) {
+            return graph;
         }
+        let local: HashMap<usize, usize> =
+            nodes.iter().enumerate().map(|(i, &node)| (node, i + 1)).collect();
+        graph.relabel(|node| local[&node])
+    }
+
+    /// Label offset of every component in the merged graph.
+    pub fn offsets(&self) -> Vec<usize> {
+        let mut offset = 0;
+        self.graphs
+            .iter()
+            .map(|graph| {
+                let current = offset;
+                offset += graph.node_count();
+                current
+            })
+            .collect()
     }
 
+    /// Edge between two nodes given by their merged labels.
     pub fn add_edge(&mut self, u: usize, v: usize, w: Option<i64>) {
-        let mut graph = Graph::new(u.max(v), self.directed);
-        graph.add_edge(u, v, w);
-        self.graphs.push(graph);
+        self.links.push((u, v, w));
+    }
+
+    /// Edge from node `u` of component `a` to node `v` of component `b`,
+    /// with `u` and `v` in the component's compacted labels `1..=n`.
+    pub fn add_edge_between(
+        &mut self,
+        (a, u): (usize, usize),
+        (b, v): (usize, usize),
+        w: Option<i64>,
+    ) {
+        let offsets = self.offsets();
+        for (c, x) in [(a, u), (b, v)] {
+            let n = self.graphs[c].node_count();
+            assert!((1..=n).contains(&x), "node {x} is outside component {c}'s labels 1..={n}");
+        }
+        self.links.push((offsets[a] + u, offsets[b] + v, w));
+    }
+
+    /// Join all components along a random tree. `shared_count` tree links
+    /// glue a node of one component onto a node of the other, making it an
+    /// articulation point; `bridge_count` links are single bridge edges; the
+    /// remaining links are two distinct edges, so they are not bridges.
+    pub fn connect(
+        &mut self,
+        bridge_count: usize,
+        shared_count: usize,
+        weight_limit: Option<(i64, i64)>,
+    ) {
+        let k = self.graphs.len();
+        assert!(k > 0, "nothing to connect");
+        assert!(
+            bridge_count + shared_count < k,
+            "bridge_count + shared_count must be below the component count"
+        );
+        assert!(self.graphs.iter().all(|g| g.node_count() > 0), "components must not be empty");
+
+        let mut rng = rng();
+        let offsets = self.offsets();
+        let sizes: Vec<usize> = self.graphs.iter().map(|g| g.node_count()).collect();
+        let weight = |rng: &mut ThreadRng| weight_limit.map(|(lo, hi)| rng.random_range(lo..=hi));
+
+        let mut kinds: Vec<MergeLink> = std::iter::repeat_n(MergeLink::Shared, shared_count)
+            .chain(std::iter::repeat_n(MergeLink::Bridge, bridge_count))
+            .chain(std::iter::repeat_n(MergeLink::Double, k - 1 - bridge_count - shared_count))
+            .collect();
+        kinds.shuffle(&mut rng);
+        let mut order: Vec<usize> = (0..k).collect();
+        order.shuffle(&mut rng);
+
+        for (i, kind) in (1..k).zip(kinds) {
+            let (p, c) = (order[rng.random_range(0..i)], order[i]);
+            let pick = |rng: &mut ThreadRng| {
+                (
+                    offsets[p] + rng.random_range(1..=sizes[p]),
+                    offsets[c] + rng.random_range(1..=sizes[c]),
+                )
+            };
+            let (a, b) = pick(&mut rng);
+            match kind {
+                MergeLink::Shared => self.shared.push((a, b)),
+                MergeLink::Bridge => self.links.push((a, b, weight(&mut rng))),
+                MergeLink::Double => {
+                    self.links.push((a, b, weight(&mut rng)));
+                    let mut second = pick(&mut rng);
+                    while second == (a, b) && sizes[p] * sizes[c] > 1 {
+                        second = pick(&mut rng);
+                    }
+                    self.links.push((second.0, second.1, weight(&mut rng)));
+                }
+            }
+        }
     }
 
     pub fn to_string(&self) -> String {
@@ -2064,18 +5105,125 @@ impl Merger {
     }
 
     pub fn to_graph(&self) -> Graph {
-        let mut max_node = 0usize;
-        for graph in &self.graphs {
-            max_node = max_node.max(graph.node_count());
+        let offsets = self.offsets();
+        let total: usize = self.graphs.iter().map(|g| g.node_count()).sum();
+
+        // Glued nodes collapse onto the smallest label, then labels are
+        // renumbered to stay consecutive.
+        let mut root: Vec<usize> = (0..=total).collect();
+        fn find(root: &mut [usize], x: usize) -> usize {
+            let mut r = x;
+            while root[r] != r {
+                r = root[r];
+            }
+            let mut x = x;
+            while root[x] != r {
+                let next = root[x];
+                root[x] = r;
+                x = next;
+            }
+            r
+        }
+        for &(a, b) in &self.shared {
+            let (ra, rb) = (find(&mut root, a), find(&mut root, b));
+            root[ra.max(rb)] = ra.min(rb);
+        }
+        let mut label = vec![0; total + 1];
+        let mut count = 0;
+        for x in 1..=total {
+            let r = find(&mut root, x);
+            if r == x {
+                count += 1;
+                label[x] = count;
+            } else {
+                label[x] = label[r];
+            }
         }
-        let mut merged = Graph::new(max_node, self.directed);
-        for graph in &self.graphs {
-            for edge in graph.iter_edges_all() {
+
+        let mut merged = Graph::new(count, self.directed);
+        for (graph, offset) in self.graphs.iter().zip(offsets) {
+            for (&node, &value) in &graph.node_values {
+                merged.node_values.entry(label[node + offset]).or_insert(value);
+            }
+            for edge in graph.iter_edges() {
                 let weight = if edge.weighted { Some(edge.w) } else { None };
-                merged.add_edge(edge.u, edge.v, weight);
+                merged.add_edge_with_attrs(
+                    label[edge.u + offset],
+                    label[edge.v + offset],
+                    weight,
+                    edge.attrs.clone(),
+                );
+            }
+        }
+        for &(u, v, w) in &self.links {
+            assert!(
+                (1..=total).contains(&u) && (1..=total).contains(&v),
+                "merged edge ({u}, {v}) is outside the merged labels 1..={total}"
+            );
+            merged.add_edge(label[u], label[v], w);
+        }
+
+        if self.shuffle {
+            merged.shuffle_labels()
+        } else {
+            merged
+        }
+    }
+
+    /// Random sizes of `component_count` components adding up to
+    /// `point_count`, each at least `min_size`.
+    pub fn random_sizes(point_count: usize, component_count: usize, min_size: usize) -> Vec<usize> {
+        assert!(component_count > 0, "component_count must be above zero");
+        assert!(
+            component_count * min_size <= point_count,
+            "point_count too small for component_count * min_size"
+        );
+        let mut rng = rng();
+        let spare = point_count - component_count * min_size;
+        let mut cuts: Vec<usize> = (1..component_count)
+            .map(|_| rng.random_range(0..=spare))
+            .collect();
+        cuts.push(0);
+        cuts.push(spare);
+        cuts.sort_unstable();
+        cuts.windows(2).map(|w| min_size + w[1] - w[0]).collect()
+    }
+
+    /// Disjoint connected components with the given sizes and `edge_count`
+    /// edges in total; edges beyond the spanning trees are spread randomly.
+    pub fn component_with_sizes(sizes: &[usize], edge_count: usize, directed: bool) -> Graph {
+        assert!(
+            !sizes.is_empty() && sizes.iter().all(|&s| s > 0),
+            "component sizes must be above zero"
+        );
+        let base_edges: usize = sizes.iter().map(|s| s - 1).sum();
+        assert!(edge_count >= base_edges, "edge_count too small for requested components");
+        let capacity: Vec<usize> = sizes
+            .iter()
+            .map(|&s| Graph::max_edge_count(s, directed, false) - (s - 1))
+            .collect();
+        assert!(
+            edge_count - base_edges <= capacity.iter().sum::<usize>(),
+            "edge_count exceeds max possible edges for these components"
+        );
+
+        let mut rng = rng();
+        let mut extra = vec![0; sizes.len()];
+        let mut open: Vec<usize> = (0..sizes.len()).filter(|&i| capacity[i] > 0).collect();
+        for _ in 0..edge_count - base_edges {
+            let j = rng.random_range(0..open.len());
+            let i = open[j];
+            extra[i] += 1;
+            if extra[i] == capacity[i] {
+                open.swap_remove(j);
             }
         }
-        merged
+
+        let graphs = sizes
+            .iter()
+            .zip(extra)
+            .map(|(&size, extra)| Graph::connected(size, size - 1 + extra, directed, None, None));
+        Merger::new(graphs, directed).to_graph()
     }
 
     pub fn component(
@@ -2086,41 +5234,12 @@ impl Merger {
     ) -> Graph {
         assert!(point_count > 0, "point_count must be above zero");
         assert!(component_count >= 1 && component_count <= point_count, "invalid component_count");
-        assert!(
-            edge_count >= point_count - component_count,
-            "edge_count too small for requested components"
-        );
 
         let mut sizes = vec![point_count / component_count; component_count];
-        for i in 0..(point_count % component_count) {
-            sizes[i] += 1;
-        }
-
-        let base_edges = point_count - component_count;
-        let mut extra_edges = edge_count - base_edges;
-        let mut graphs = Vec::new();
-
-        for size in sizes {
-            let extra = if extra_edges == 0 { 0 } else { 1 };
-            if extra_edges > 0 {
-                extra_edges -= 1;
-            }
-            let edges_for_component = size.saturating_sub(1) + extra;
-            let graph = Graph::connected(size, edges_for_component, directed, None, None);
-            graphs.push(graph);
-        }
-
-        let mut merged = Graph::new(point_count, directed);
-        let mut offset = 0usize;
-        for graph in graphs {
-            for edge in graph.iter_edges_all() {
-                let weight = if edge.weighted { Some(edge.w) } else { None };
-                merged.add_edge(edge.u + offset, edge.v + offset, weight);
-            }
-            offset += graph.node_count();
+        for size in sizes.iter_mut().take(point_count % component_count) {
+            *size += 1;
         }
-
-        merged
+        Merger::component_with_sizes(&sizes, edge_count, directed)
     }
 }
 
@@ -2140,3 +5259,576 @@ impl<T: std::fmt::Display> std::fmt::Display for GraphMatrix<T> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjacency(g: &Graph) -> Vec<Vec<(usize, i64)>> {
+        let mut adj = vec![Vec::new(); g.node_count() + 1];
+        for e in g.iter_edges_all() {
+            adj[e.u].push((e.v, e.weight().unwrap()));
+        }
+        adj
+    }
+
+    #[derive(Clone, Copy)]
+    enum SpfaQueue {
+        Fifo,
+        Slf,
+        Lll,
+    }
+
+    /// Distances from node 1 and the number of successful relaxations.
+    fn spfa_relaxations(g: &Graph, queue_kind: SpfaQueue) -> (Vec<i64>, usize) {
+        let adj = adjacency(g);
+        let mut dist = vec![i64::MAX; adj.len()];
+        let mut in_queue = vec![false; adj.len()];
+        let mut queue = std::collections::VecDeque::from([1]);
+        let (mut relaxations, mut queued_sum) = (0, 0i128);
+        dist[1] = 0;
+        in_queue[1] = true;
+        while !queue.is_empty() {
+            if let SpfaQueue::Lll = queue_kind {
+                while dist[queue[0]] as i128 * queue.len() as i128 > queued_sum {
+                    queue.rotate_left(1);
+                }
+            }
+            let u = queue.pop_front().unwrap();
+            in_queue[u] = false;
+            queued_sum -= dist[u] as i128;
+            for &(v, w) in &adj[u] {
+                if dist[u] + w >= dist[v] {
+                    continue;
+                }
+                if in_queue[v] {
+                    queued_sum -= dist[v] as i128;
+                }
+                dist[v] = dist[u] + w;
+                relaxations += 1;
+                if in_queue[v] {
+                    queued_sum += dist[v] as i128;
+                    continue;
+                }
+                in_queue[v] = true;
+                queued_sum += dist[v] as i128;
+                match queue_kind {
+                    SpfaQueue::Slf if queue.front().is_some_and(|&f| dist[v] < dist[f]) => {
+                        queue.push_front(v)
+                    }
+                    _ => queue.push_back(v),
+                }
+            }
+        }
+        (dist, relaxations)
+    }
+
+    /// Dijkstra from node 1 with an optional visited flag and no stale-entry
+    /// check. Returns the distances and the number of edges scanned.
+    fn dijkstra_scans(g: &Graph, visited_flag: bool) -> (Vec<i64>, usize) {
+        use std::{cmp::Reverse, collections::BinaryHeap};
+        let adj = adjacency(g);
+        let mut dist = vec![i64::MAX; adj.len()];
+        let mut visited = vec![false; adj.len()];
+        let mut heap = BinaryHeap::from([Reverse((0i64, 1usize))]);
+        let mut scans = 0;
+        dist[1] = 0;
+        while let Some(Reverse((d, u))) = heap.pop() {
+            if visited_flag {
+                if visited[u] {
+                    continue;
+                }
+                visited[u] = true;
+            }
+            for &(v, w) in &adj[u] {
+                scans += 1;
+                if d + w < dist[v] {
+                    dist[v] = d + w;
+                    heap.push(Reverse((dist[v], v)));
+                }
+            }
+        }
+        (dist, scans)
+    }
+
+    #[test]
+    fn test_hack_spfa_grid_shape() {
+        let g = Graph::hack_spfa_grid(3, 4, Some((5, 9)));
+        assert_eq!(g.node_count(), 12);
+        assert_eq!(g.edge_count(), 3 * 3 + 2 * 4);
+        assert!(g.iter_edges().all(|e| (5..=9).contains(&e.weight().unwrap())));
+
+        let g = Graph::hack_spfa_lll(3, 4, Some((5, 9)));
+        assert_eq!(g.node_count(), 13);
+        assert!(g.is_valid());
+    }
+
+    #[test]
+    fn test_hack_shortest_path_generators() {
+        for g in [
+            Graph::hack_spfa_slf(10),
+            Graph::hack_dijkstra_no_visited(10),
+            Graph::hack_dijkstra_negative(10),
+        ] {
+            assert_eq!(g.node_count(), 10);
+            assert!(g.is_directed());
+            assert!(g.is_valid());
+        }
+    }
+
+    #[test]
+    fn test_hack_spfa_grid_defeats_fifo_spfa() {
+        let g = Graph::hack_spfa_grid(10, 300, None);
+        let nm = g.node_count() * g.edge_count();
+        let (_, relaxations) = spfa_relaxations(&g, SpfaQueue::Fifo);
+        assert!(relaxations > nm / 40, "{relaxations} relaxations");
+    }
+
+    #[test]
+    fn test_hack_spfa_lll_defeats_lll() {
+        let g = Graph::hack_spfa_lll(10, 300, None);
+        let nm = g.node_count() * g.edge_count();
+        let (_, relaxations) = spfa_relaxations(&g, SpfaQueue::Lll);
+        assert!(relaxations > nm / 70, "{relaxations} relaxations");
+    }
+
+    #[test]
+    fn test_hack_spfa_slf_defeats_every_queue() {
+        let g = Graph::hack_spfa_slf(2000);
+        let nm = g.node_count() * g.edge_count();
+        for queue in [SpfaQueue::Fifo, SpfaQueue::Slf, SpfaQueue::Lll] {
+            let (_, relaxations) = spfa_relaxations(&g, queue);
+            assert!(relaxations > nm / 16, "{relaxations} relaxations");
+        }
+    }
+
+    #[test]
+    fn test_hack_dijkstra_no_visited_rescans_hub() {
+        let g = Graph::hack_dijkstra_no_visited(2000);
+        let nm = g.node_count() * g.edge_count();
+        let (dist, scans) = dijkstra_scans(&g, false);
+        assert!(scans > nm / 16, "{scans} scans");
+        let (visited_dist, visited_scans) = dijkstra_scans(&g, true);
+        assert_eq!((visited_dist, visited_scans), (dist, g.edge_count()));
+    }
+
+    #[test]
+    fn test_hack_dijkstra_negative_breaks_dijkstra() {
+        let g = Graph::hack_dijkstra_negative(2000);
+        let nm = g.node_count() * g.edge_count();
+        let (correct, _) = spfa_relaxations(&g, SpfaQueue::Fifo);
+        let (dist, scans) = dijkstra_scans(&g, false);
+        assert_eq!(dist, correct);
+        assert!(scans > nm / 16, "{scans} scans");
+        assert_ne!(dijkstra_scans(&g, true).0, correct);
+    }
+
+    fn cycle_weight(g: &Graph, cycle: &[usize]) -> i64 {
+        (0..cycle.len())
+            .map(|i| {
+                let (u, v) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                g.iter_edges_all()
+                    .find(|e| e.u == u && e.v == v)
+                    .and_then(|e| e.weight())
+                    .expect("cycle edge missing")
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_planted_cycles() {
+        let (g, cycle) = Graph::negative_cycle_graph(20, 60, 5, (-10, 10), Some(1));
+        assert_eq!(g.edge_count(), 60);
+        assert!(cycle_weight(&g, &cycle) < 0);
+
+        let (g, cycle) = Graph::zero_cycle_graph(20, 60, 4, (-10, 10));
+        assert_eq!(g.edge_count(), 60);
+        assert_eq!(cycle_weight(&g, &cycle), 0);
+
+        let g = Graph::no_negative_cycle_graph(20, 60, (-10, 10));
+        assert!(g.iter_edges().all(|e| (-10..=10).contains(&e.weight().unwrap())));
+    }
+
+    #[test]
+    fn test_cactus_and_pseudotree() {
+        for kind in [CactusKind::Vertex, CactusKind::Edge] {
+            let g = Graph::cactus(30, 4, (3, 6), kind, Some((1, 5)), None, true);
+            assert_eq!(g.node_count(), 30);
+            assert_eq!(g.edge_count(), 29 + 4);
+        }
+        let g = Graph::pseudotree(20, 5, None, false, None, true);
+        assert_eq!(g.edge_count(), 20);
+    }
+
+    #[test]
+    fn test_functional_graph() {
+        let g = Graph::functional_graph(10, &[3, 1], 2, None, None, false);
+        let f = g.to_functional().unwrap();
+        assert_eq!(&f[..4], &[2, 3, 1, 4]);
+        assert!(f[4..].iter().all(|&x| x < 11));
+        assert!(Graph::chain(3, None, true, None).to_functional().is_none());
+    }
+
+    #[test]
+    fn test_bipartite_generators() {
+        let g = Graph::bipartite_with_matching(6, 8, 20, 4, None, None);
+        assert_eq!(g.edge_count(), 20);
+        let (left, right) = g.to_bipartite_adj(6);
+        assert_eq!((left.len(), right.len()), (6, 8));
+        assert_eq!(left.iter().map(|l| l.len()).sum::<usize>(), 20);
+
+        let g = Graph::regular_bipartite(5, 2, 20, Some((1, 1)), None);
+        let (left, right) = g.to_bipartite_adj(5);
+        assert!(left.iter().chain(right.iter()).all(|l| l.len() == 2));
+    }
+
+    #[test]
+    fn test_k_regular() {
+        let (nodes, matrix) = Graph::k_regular(12, 3, 50, None, None).to_matrix(0);
+        assert_eq!(nodes.len(), 12);
+        assert!(matrix.iter().all(|row| row.iter().sum::<i64>() == 3));
+        assert!((0..12).all(|i| matrix[i][i] == 0));
+
+        let (_, matrix) = Graph::k_regular_directed(9, 4, 50, None, None).to_matrix(0);
+        assert!(matrix.iter().all(|row| row.iter().sum::<i64>() == 4));
+        assert!((0..9).all(|j| matrix.iter().map(|row| row[j]).sum::<i64>() == 4));
+    }
+
+    #[test]
+    fn test_degree_sequence_switching() {
+        assert!(SwitchGraph::is_graphical(&[3, 3, 2, 2, 2]));
+        assert!(!SwitchGraph::is_graphical(&[3, 3, 1, 1]));
+        assert!(!SwitchGraph::is_graphical(&[2, 1, 1, 1]));
+
+        let sequence = Graph::power_law_degree_sequence(40, 2.5, 1, 10);
+        assert_eq!(sequence.len(), 40);
+        assert!(sequence.iter().all(|&d| (1..=10).contains(&d)));
+        assert!(SwitchGraph::is_graphical(&sequence));
+
+        // Havel-Hakimi may build several cycles; mixing must not add more.
+        let mut switch = SwitchGraph::from_undirected_degree_sequence(&[2; 12], false, false).unwrap();
+        let components = switch.component_count();
+        let options = SwitchOptions { steps: 500, keep_connected: true };
+        switch.mix(&options, false, false);
+        assert!(switch.component_count() <= components);
+
+        let cycle: Vec<(i32, i32)> = (0..12).map(|i| (i, (i + 1) % 12)).collect();
+        let mut switch = SwitchGraph::new(cycle, false);
+        switch.mix(&options, false, false);
+        assert!(switch.is_connected());
+        let mut degrees = [0; 12];
+        for (u, v) in switch.iter_edges() {
+            assert_ne!(u, v);
+            degrees[u] += 1;
+            degrees[v] += 1;
+        }
+        assert_eq!(degrees, [4; 12]);
+
+        let degree_sum: usize = sequence.iter().sum();
+        let graph = Graph::from_degree_sequence(
+            DegreeSequence::Undirected(&sequence),
+            false,
+            false,
+            Some(options),
+        )
+        .unwrap();
+        assert_eq!(graph.iter_edges().count() * 2, degree_sum);
+    }
+
+    #[cfg(feature = "polygon")]
+    #[test]
+    fn test_planar_generators() {
+        let square = [Point::new(0, 0), Point::new(2, 0), Point::new(2, 2), Point::new(0, 2), Point::new(1, 1)];
+        assert_eq!(Graph::delaunay(&square, None, None).iter_edges().count(), 8);
+        let line: Vec<Point> = (0..4).map(|i| Point::new(i, i)).collect();
+        assert_eq!(Graph::delaunay(&line, None, None).iter_edges().count(), 3);
+
+        let (graph, points) = Graph::random_triangulation(30, (0, 100), (0, 100), None, None);
+        assert_eq!(points.len(), 30);
+        assert!(graph.iter_edges().count() <= 3 * 30 - 6);
+
+        let (graph, points) = Graph::maximal_planar(40, 1000, 200, Some((1, 9)), None, true);
+        assert_eq!(points.len(), 40);
+        assert_eq!(graph.iter_edges().count(), 3 * 40 - 6);
+
+        let (graph, _) = Graph::outerplanar(20, 30, None, None, true);
+        assert_eq!(graph.iter_edges().count(), 30);
+        assert!(graph.iter_edges().all(|e| e.u != e.v));
+    }
+
+    #[test]
+    fn test_graph_analysis() {
+        // Two triangles joined by the bridge 3 - 4, plus an isolated node 7.
+        let mut g = Graph::new(7, false);
+        g.add_edges([(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 6), (6, 4)]);
+        assert_eq!(g.connected_components(), vec![vec![1, 2, 3, 4, 5, 6], vec![7]]);
+        assert_eq!(g.bridges(), vec![(3, 4)]);
+        assert_eq!(g.articulation_points(), vec![3, 4]);
+        assert!(!g.is_bipartite());
+        assert_eq!(g.find_cycle().map(|c| c.len()), Some(3));
+        assert_eq!(g.bfs_distances(1)[&6], 3);
+        assert_eq!(g.diameter(), None);
+
+        let tree = Graph::chain(5, None, false, None);
+        assert!(tree.is_tree() && tree.is_bipartite());
+        assert_eq!(tree.diameter(), Some(4));
+        assert_eq!(tree.tree_depth(3), 2);
+
+        let mut dag = Graph::new(4, true);
+        dag.add_edges([(1usize, 2usize, 5i64), (1, 3, 1), (3, 2, 1), (2, 4, -2)]);
+        assert_eq!(dag.topological_order(), Some(vec![1, 3, 2, 4]));
+        assert_eq!(dag.dag_longest_path(), Some((5, vec![1, 2])));
+        assert_eq!(dag.bellman_ford(1).map(|d| d[&4]), Some(0));
+        assert_eq!(dag.strongly_connected_components().len(), 4);
+
+        dag.add_edge(4, 1, Some(-1));
+        assert_eq!(dag.bellman_ford(1), None);
+        assert_eq!(dag.strongly_connected_components(), vec![vec![1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn test_weight_constraints_and_mst() {
+        let options = GraphGenOptions {
+            weight_mode: WeightMode::Permutation,
+            ..GraphGenOptions::default()
+        };
+        let g = Graph::graph_with_options(10, 25, options, None, None);
+        let mut weights: Vec<i64> = g.iter_edges().filter_map(|e| e.weight()).collect();
+        weights.sort_unstable();
+        assert_eq!(weights, (1..=25).collect::<Vec<i64>>());
+
+        let mut chain = Graph::chain(6, Some((1, 9)), false, None);
+        chain.constrain_weights(WeightMode::Distinct, WeightOrder::Increasing, Some((1, 5)));
+        let mut edges: Vec<(usize, i64)> = chain.iter_edges().map(|e| (e.u.min(e.v), e.w)).collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]);
+
+        let (g, total) = Graph::unique_mst_graph(30, 80, (1, 5));
+        assert_eq!(g.edge_count(), 80);
+        assert!(g.is_connected());
+        assert!(total >= 29);
+        let (g, total) = Graph::tied_mst_graph(30, 80, (7, 7));
+        assert_eq!(total, 29 * 7);
+        assert!(g.iter_edges().all(|e| e.w == 7));
+    }
+
+    #[test]
+    fn test_node_values_and_edge_attrs() {
+        let mut g = Graph::new(3, false);
+        g.add_edge_with_attrs(1, 2, Some(5), vec![7, 8]);
+        g.add_edge(2, 3, Some(1));
+        g.set_node_value(1, 10);
+        g.set_node_value(3, 30);
+        assert_eq!(g.node_value(2), None);
+        assert_eq!(g.node_values_line(), "10 0 30");
+
+        let text = g.to_string(false, None, None);
+        assert_eq!(text.lines().next(), Some("10 0 30"));
+        assert!(text.lines().any(|line| line == "1 2 5 7 8"));
+
+        g.assign_edge_attrs(Graph::weight_uniform(4, 4));
+        assert!(g.iter_edges_all().all(|e| e.attrs().last() == Some(&4)));
+        g.assign_node_values(Graph::weight_uniform(-1, -1));
+        assert_eq!(g.node_values_line(), "-1 -1 -1");
+    }
+
+    #[test]
+    fn test_transforms_keep_edges_and_attrs() {
+        let mut g = Graph::new(3, false);
+        g.add_edge_with_attrs(1, 2, Some(5), vec![7]);
+        g.add_edge_with_attrs(2, 3, None, vec![8, 9]);
+        let nodes: std::collections::HashSet<usize> = [1, 2, 3].into_iter().collect();
+        for t in [
+            g.relabel(|node| node + 1),
+            g.offset_labels(0),
+            g.subgraph_by_nodes(&nodes),
+            g.filter_edges(|_| true),
+        ] {
+            assert_eq!(t.edge_count(), 2);
+            assert_eq!(t.edge_count_all(), 4);
+            let mut attrs: Vec<Vec<i64>> = t.iter_edges().map(|e| e.attrs().to_vec()).collect();
+            attrs.sort();
+            assert_eq!(attrs, vec![vec![7], vec![8, 9]]);
+        }
+
+        let mut d = Graph::new(2, true);
+        d.add_edge_with_attrs(1, 2, Some(1), vec![3]);
+        d.add_edge(2, 1, Some(2));
+        assert_eq!(d.relabel(|node| 3 - node).edge_count(), 2);
+        assert_eq!(d.filter_edges(|e| e.weight() == Some(1)).edge_count(), 1);
+    }
+
+    #[test]
+    fn test_graph_matrix_conversions() {
+        let mut g = Graph::new(3, false);
+        g.add_edge(1, 2, Some(4));
+        g.add_edge(1, 2, Some(3));
+        g.add_edge(2, 3, Some(10));
+        let m = g.to_graph_matrix(-1);
+        assert!(m.is_symmetric());
+        assert_eq!(m[(0, 1)], 3);
+        assert_eq!(m.format(Some("INF"), true), "INF   3 INF\n  3 INF  10\nINF  10 INF");
+        assert_eq!(m.to_graph(false).edge_count(), 2);
+
+        let m = GraphMatrix::random(8, 0.5, true, i64::MAX, (1, 9));
+        assert!(m.is_symmetric());
+        assert!((0..8).all(|i| m[(i, i)] == 0));
+        assert!(m.iter().flatten().all(|&w| w == i64::MAX || (0..=9).contains(&w)));
+        let back = GraphMatrix::from_graph(&m.to_graph(false), i64::MAX);
+        assert!((0..8).all(|u| (0..8).all(|v| u == v || back[(u, v)] == m[(u, v)])));
+
+        let m = GraphMatrix::random(6, 1.0, false, 0, (1, 1));
+        assert_eq!(m.to_graph(true).edge_count(), 30);
+    }
+
+    #[test]
+    fn test_merger_offsets_and_links() {
+        let trees = || (0..2).map(|_| Graph::tree(4, 0.0, 0.0, None, false, None, None));
+        let merged = Merger::new(trees(), false).to_graph();
+        assert_eq!(merged.node_count(), 8);
+        assert_eq!(merged.edge_count(), 6);
+        assert_eq!(merged.connected_components().len(), 2);
+
+        let mut bridged = Merger::new(trees(), false);
+        bridged.connect(1, 0, None);
+        let graph = bridged.to_graph();
+        assert!(graph.is_tree());
+        assert_eq!(graph.bridges().len(), 7);
+
+        let mut glued = Merger::new(trees(), false);
+        glued.connect(0, 1, None);
+        let graph = glued.to_graph();
+        assert_eq!(graph.node_count(), 7);
+        assert!(graph.is_tree());
+
+        let sizes = Merger::random_sizes(30, 4, 3);
+        assert_eq!(sizes.len(), 4);
+        assert_eq!(sizes.iter().sum::<usize>(), 30);
+        assert!(sizes.iter().all(|&s| s >= 3));
+        let graph = Merger::component_with_sizes(&sizes, 40, false);
+        assert_eq!(graph.edge_count(), 40);
+        assert_eq!(graph.connected_components().len(), 4);
+    }
+
+    #[test]
+    fn test_merger_compacts_component_labels() {
+        // A path 5 - 7 - 9 plus a component with a gap in its labels.
+        let mut path = Graph::new(9, false);
+        path.add_edge(5, 7, Some(1));
+        path.add_edge(7, 9, Some(2));
+        path.set_node_value(9, 90);
+        let nodes: std::collections::HashSet<usize> = [5, 7, 9].into_iter().collect();
+        let path = path.subgraph_by_nodes(&nodes);
+        let mut gapped = Graph::with_nodes([2, 4], false);
+        gapped.add_edge(2, 4, Some(3));
+
+        let mut merger = Merger::new([path, gapped.offset_labels(10)], false);
+        assert_eq!(merger.offsets(), vec![0, 3]);
+        merger.add_edge_between((0, 3), (1, 1), Some(4));
+        let graph = merger.to_graph();
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 4);
+        assert!(graph.is_tree());
+        assert_eq!(graph.node_value(3), Some(90));
+        assert!(graph.iter_edges().any(|e| (e.u, e.v, e.weight()) == (3, 4, Some(4))));
+    }
+
+    #[test]
+    fn test_eulerian_and_hamiltonian_generators() {
+        let (graph, walk) = Graph::eulerian(10, 25, false, true, None, None);
+        assert_eq!(graph.edge_count(), 25);
+        assert!(graph.is_connected());
+        assert_eq!(walk.first(), walk.last());
+        let mut degree: HashMap<usize, usize> = HashMap::new();
+        for pair in walk.windows(2) {
+            *degree.entry(pair[0]).or_insert(0) += 1;
+            *degree.entry(pair[1]).or_insert(0) += 1;
+        }
+        assert!(degree.values().all(|&d| d.is_multiple_of(2)));
+
+        let (graph, walk) = Graph::eulerian(10, 25, true, false, None, None);
+        assert_eq!(graph.edge_count(), 25);
+        assert_ne!(walk.first(), walk.last());
+
+        let (graph, order) = Graph::hamiltonian(12, 30, false, true, None, None);
+        assert_eq!(graph.edge_count(), 30);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (1..=12).collect::<Vec<_>>());
+        let mut seen = std::collections::HashSet::new();
+        for edge in graph.iter_edges() {
+            seen.insert((edge.u.min(edge.v), edge.u.max(edge.v)));
+        }
+        for i in 0..12 {
+            let (u, v) = (order[i], order[(i + 1) % 12]);
+            assert!(seen.contains(&(u.min(v), u.max(v))));
+        }
+
+        let graph = Graph::non_hamiltonian(9, 15, false, true, None, None);
+        assert_eq!(graph.edge_count(), 15);
+        assert!(graph.is_connected());
+        let (a, b) = graph.bipartition().unwrap();
+        assert!(a.len().abs_diff(b.len()) >= 2);
+    }
+
+    #[test]
+    fn test_tournament_generators() {
+        let graph = Graph::tournament(8, None, None);
+        assert_eq!(graph.edge_count(), 28);
+        assert_eq!(graph.score_sequence().iter().sum::<usize>(), 28);
+
+        let (graph, order) = Graph::transitive_tournament(6, None, None);
+        let scores = graph.score_sequence();
+        for (rank, &node) in order.iter().enumerate() {
+            assert_eq!(scores[node - 1], 5 - rank);
+        }
+        assert!(graph.topological_order().is_some());
+
+        let (graph, _) = Graph::hamiltonian_tournament(7, None, None);
+        assert_eq!(graph.strongly_connected_components().len(), 1);
+
+        let (graph, king) = Graph::king_tournament(9, None, None);
+        let dist = graph.bfs_distances(king);
+        assert_eq!(dist.len(), 9);
+        assert!(dist.values().all(|&d| d <= 2));
+        assert!(graph.score_sequence()[king - 1] < 8);
+
+        let scores = vec![2, 2, 2, 2, 2];
+        assert!(Graph::is_score_sequence(&scores));
+        assert!(!Graph::is_score_sequence(&[0, 0, 3]));
+        let graph = Graph::tournament_from_scores(&scores, Some((1, 9)), None);
+        assert_eq!(graph.score_sequence(), scores);
+    }
+
+    #[test]
+    fn test_forest_metadata() {
+        let forest = Graph::forest_with_roots(20, 4, None, true, None);
+        assert_eq!(forest.graph.edge_count(), 16);
+        assert_eq!(forest.roots.len(), 4);
+        assert_eq!(forest.sizes.iter().sum::<usize>(), 20);
+        for &root in &forest.roots {
+            assert_eq!(forest.parent[root], 0);
+        }
+        assert_eq!(forest.parent.iter().skip(1).filter(|&&p| p == 0).count(), 4);
+        assert_eq!(forest.parent_line().split_whitespace().count(), 20);
+
+        let shapes = [
+            TreeShape { chain: 1.0, flower: 0.0 },
+            TreeShape { chain: 0.0, flower: 1.0 },
+        ];
+        let forest = Graph::forest_with_shapes(12, &shapes, Some((1, 5)), false, None);
+        assert_eq!(forest.depths[0], forest.sizes[0] - 1);
+        assert_eq!(forest.depths[1], forest.sizes[1].min(2) - 1);
+        assert_eq!(forest.graph.connected_components().len(), 2);
+
+        // Chain and flower rounded up together used to overflow the tree.
+        let halves = [TreeShape { chain: 0.5, flower: 0.5 }; 2];
+        for n in 2..=12 {
+            let tree = Graph::tree(n, 0.5, 0.5, None, false, None, None);
+            assert!(tree.node_count() == n && tree.is_tree());
+            let forest = Graph::forest_with_shapes(n, &halves, None, false, None);
+            assert_eq!(forest.graph.edge_count(), n - 2);
+            assert_eq!(forest.graph.node_count(), n);
+        }
+    }
+}
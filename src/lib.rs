@@ -6,6 +6,8 @@ pub mod testcase;
 pub mod graph;
 #[cfg(feature = "math")]
 pub mod math;
+#[cfg(feature = "bigint")]
+pub mod bigint;
 #[cfg(feature = "sequence")]
 pub mod sequence;
 #[cfg(feature = "vector")]
@@ -1,5 +1,10 @@
 //! This is a module that supports some useful maths functions.
 
+use rand::{seq::SliceRandom, Rng};
+
+#[cfg(feature = "bigint")]
+use crate::bigint::BigUint;
+
 const DIGIT_FACT: [u64; 10] = [
     1,          // 0!
     1,          // 1!
@@ -126,18 +131,28 @@ pub fn is_pal_u64(n: u64) -> bool {
     x == reversed || x == reversed / 10
 }
 
+/// Sum of all divisors of `n`, from its factorization. Panics when the sum
+/// does not fit in `u64`, as for highly composite `n` near `u64::MAX`; use
+/// `divisor_sum_u128` there.
 pub fn divisor_sum(n: u64) -> u64 {
-    if n == 0 { return 0; }
+    u64::try_from(divisor_sum_u128(n)).expect("divisor sum overflows u64")
+}
 
-    let mut i: u64 = 1;
-    let mut sum: u64 = 0;
+/// `divisor_sum` for every `u64`: sigma(n) < 2^70, so it always fits.
+pub fn divisor_sum_u128(n: u64) -> u128 {
+    if n == 0 { return 0; }
 
-    while i * i <= n {
-        if n % i == 0 { sum += i + n / i; }
-        if i * i == n { sum -= i; }
-        i += 1;
+    let mut sum: u128 = 1;
+    for (p, e) in factor(n) {
+        let p = p as u128;
+        let mut term = 1u128;
+        let mut power = 1u128;
+        for _ in 0..e {
+            power *= p;
+            term += power;
+        }
+        sum *= term;
     }
-
     sum
 }
 
@@ -259,28 +274,150 @@ pub fn fibonacci_range(start: u64, end: u64) -> Vec<u64> {
     res
 }
 
+/// `F(n) mod m` by fast doubling, for any `n` up to `u64::MAX`.
+/// Example: fibonacci_mod(10, 7) -> 6, since F(10) = 55.
+pub fn fibonacci_mod(n: u64, m: u64) -> u64 {
+    assert!(m > 0, "modulus must be above zero");
+    fn pair(n: u64, m: u128) -> (u128, u128) {
+        if n == 0 {
+            return (0, 1 % m);
+        }
+        let (a, b) = pair(n / 2, m);
+        let c = a * ((2 * b + m - a) % m) % m;
+        let d = (a * a % m + b * b % m) % m;
+        if n.is_multiple_of(2) {
+            (c, d)
+        } else {
+            (d, (c + d) % m)
+        }
+    }
+    pair(n, m as u128).0 as u64
+}
+
+/// n-th term modulo `m` of `a[i] = coeffs[0] * a[i-1] + ... + coeffs[k-1] * a[i-k]`
+/// with `a[0..k] = init`, by raising the `k x k` companion matrix to the
+/// `(n - k + 1)`-th power. Example: linear_recurrence(&[1, 1], &[0, 1], n, m)
+/// is `fibonacci_mod(n, m)`.
+pub fn linear_recurrence(coeffs: &[u64], init: &[u64], n: u64, m: u64) -> u64 {
+    let k = coeffs.len();
+    assert_eq!(init.len(), k, "need one initial term per coefficient");
+    assert!(m > 0 && m <= i64::MAX as u64, "modulus must be in 1..=i64::MAX");
+    if k == 0 {
+        return 0;
+    }
+    if n < k as u64 {
+        return init[n as usize] % m;
+    }
+    let mut companion = vec![vec![0i64; k]; k];
+    for (j, &c) in coeffs.iter().enumerate() {
+        companion[0][j] = (c % m) as i64;
+    }
+    for (i, row) in companion.iter_mut().enumerate().skip(1) {
+        row[i - 1] = 1;
+    }
+    let power = Matrix::from_rows(companion).pow_mod(n - k as u64 + 1, m);
+    // The state vector holds a[k-1], ..., a[0].
+    let state = Matrix::from_rows(init.iter().rev().map(|&x| vec![(x % m) as i64]).collect());
+    power.mul_mod(&state, m).as_rows()[0][0] as u64
+}
+
+/// Shortest linear recurrence over the prime field `Z_p` that generates
+/// `seq`, in the coefficient layout of `linear_recurrence`. `2k` terms are
+/// enough to recover a recurrence of order `k`.
+/// Example: berlekamp_massey(&[0, 1, 1, 2, 3, 5], p) -> [1, 1].
+pub fn berlekamp_massey(seq: &[u64], p: u64) -> Vec<u64> {
+    let n = seq.len();
+    let s: Vec<u64> = seq.iter().map(|&x| x % p).collect();
+    // Connection polynomials: current `c` and the one before the last
+    // length change `b`, whose discrepancy was `bd`.
+    let (mut c, mut b) = (vec![0u64; n + 1], vec![0u64; n + 1]);
+    c[0] = 1;
+    b[0] = 1;
+    let (mut len, mut shift, mut bd) = (0usize, 0usize, 1u64);
+    for i in 0..n {
+        shift += 1;
+        let d = (1..=len).fold(s[i], |acc, j| (acc + mod_mul(c[j], s[i - j], p)) % p);
+        if d == 0 {
+            continue;
+        }
+        let prev = c.clone();
+        let coef = mod_mul(d, inverse_mod_u64(bd, p).expect("modulus must be prime"), p);
+        for j in shift..=n {
+            c[j] = (c[j] + p - mod_mul(coef, b[j - shift], p)) % p;
+        }
+        if 2 * len > i {
+            continue;
+        }
+        len = i + 1 - len;
+        b = prev;
+        bd = d;
+        shift = 0;
+    }
+    c[1..=len].iter().map(|&x| (p - x) % p).collect()
+}
+
+/// Term `n` of a sequence known only by its first terms: recovers the
+/// recurrence with `berlekamp_massey` and evaluates it with `linear_recurrence`.
+pub fn guess_nth_term(seq: &[u64], n: u64, p: u64) -> u64 {
+    let coeffs = berlekamp_massey(seq, p);
+    linear_recurrence(&coeffs, &seq[..coeffs.len()], n, p)
+}
+
+/// Pisano period: the period of `F(n) mod m`. Combines prime powers by lcm;
+/// for `p^e` it starts from the known multiple `p^(e-1) * pi(p)` (with
+/// `pi(p) | p - 1` or `2(p + 1)`) and strips prime factors while the
+/// sequence still returns to `0, 1`. The period never exceeds `6m`.
+/// Example: pisano_period(10) -> 60.
+pub fn pisano_period(m: u64) -> u64 {
+    assert!(m > 0 && m <= u64::MAX / 6, "modulus must be in 1..=u64::MAX / 6");
+    let is_period = |t: u64, q: u64| fibonacci_mod(t, q) == 0 && fibonacci_mod(t + 1, q) == 1 % q;
+    let mut res = 1u64;
+    for (p, e) in factor(m) {
+        let q = p.pow(e);
+        let base = match p {
+            2 => 3,
+            5 => 20,
+            _ if matches!(p % 10, 1 | 9) => p - 1,
+            _ => 2 * (p + 1),
+        };
+        let mut t = base * p.pow(e - 1);
+        for (r, _) in factor(t) {
+            while t.is_multiple_of(r) && is_period(t / r, q) {
+                t /= r;
+            }
+        }
+        res = res / gcd(res, t) * t;
+    }
+    res
+}
+
+/// Primality test, exact for every `u64`.
 pub fn is_prime(n: u64) -> bool {
+    is_prime_u64(n)
+}
+
+/// Deterministic primality test for every `u64`: trial division by small
+/// primes, then Miller-Rabin with bases known to have no `u64` liar.
+pub fn is_prime_u64(n: u64) -> bool {
     if n < 2 {
         return false;
     }
-    if n % 2 == 0 {
-        return n == 2;
-    }
-    let mut i = 3u64;
-    while i <= n / i {
-        if n % i == 0 {
-            return false;
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
         }
-        i += 2;
     }
-    true
+    if n < 53 * 53 {
+        return true;
+    }
+    miller_rabin(n, 0)
 }
 
-fn mod_mul(a: u64, b: u64, m: u64) -> u64 {
+const fn mod_mul(a: u64, b: u64, m: u64) -> u64 {
     ((a as u128 * b as u128) % m as u128) as u64
 }
 
-fn mod_pow(mut a: u64, mut d: u64, m: u64) -> u64 {
+const fn mod_pow(mut a: u64, mut d: u64, m: u64) -> u64 {
     let mut res = 1u64;
     while d > 0 {
         if d & 1 == 1 {
@@ -306,6 +443,10 @@ fn miller_rabin_pass(a: u64, s: u64, d: u64, n: u64) -> bool {
     false
 }
 
+/// Miller-Rabin with the first `repeat_time` of the bases 2, 3, 5, 7, 11,
+/// 13, 17 (the first four are exact below 3.2e9, all seven below 3.4e14).
+/// `repeat_time == 0` uses Sinclair's seven bases instead, which have no
+/// strong liar below 2^64, so `miller_rabin(n, 0)` is exact for every `u64`.
 pub fn miller_rabin(n: u64, repeat_time: u32) -> bool {
     if n < 4 {
         return n == 2 || n == 3;
@@ -321,14 +462,15 @@ pub fn miller_rabin(n: u64, repeat_time: u32) -> bool {
         s += 1;
     }
 
-    let bases: [u64; 7] = [2, 3, 5, 7, 11, 13, 17];
-    let rounds = if repeat_time == 0 {
-        bases.len() as u32
+    const PRIME_BASES: [u64; 7] = [2, 3, 5, 7, 11, 13, 17];
+    const SINCLAIR_BASES: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+    let bases: &[u64] = if repeat_time == 0 {
+        &SINCLAIR_BASES
     } else {
-        repeat_time.min(bases.len() as u32)
+        &PRIME_BASES[..(repeat_time as usize).min(PRIME_BASES.len())]
     };
 
-    for &a in bases.iter().take(rounds as usize) {
+    for &a in bases {
         let a = a % n;
         if a == 0 {
             continue;
@@ -340,154 +482,2341 @@ pub fn miller_rabin(n: u64, repeat_time: u32) -> bool {
     true
 }
 
+/// Prime factorization as `(prime, exponent)` pairs in increasing order.
+/// Small primes are divided out directly and the rest is split with
+/// Pollard-Brent, so any `u64` factors quickly.
 pub fn factor(mut n: u64) -> Vec<(u64, u32)> {
     let mut res = Vec::new();
     if n < 2 {
         return res;
     }
-    let mut p = 2u64;
-    while p * p <= n {
-        if n % p == 0 {
-            let mut e = 0u32;
-            while n % p == 0 {
-                n /= p;
-                e += 1;
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            let mut e = 0u32;
+            while n.is_multiple_of(p) {
+                n /= p;
+                e += 1;
+            }
+            res.push((p, e));
+        }
+    }
+
+    let mut primes = Vec::new();
+    let mut pending = vec![n];
+    while let Some(m) = pending.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime_u64(m) {
+            primes.push(m);
+            continue;
+        }
+        let d = pollard_rho(m);
+        pending.push(d);
+        pending.push(m / d);
+    }
+    primes.sort_unstable();
+    for p in primes {
+        match res.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => res.push((p, 1)),
+        }
+    }
+    res
+}
+
+/// A non-trivial factor of the composite `n`, found with Brent's variant of
+/// Pollard's rho.
+pub fn pollard_rho(n: u64) -> u64 {
+    assert!(n > 3 && !is_prime_u64(n), "pollard_rho needs a composite number");
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    let root = n.isqrt();
+    if root * root == n {
+        return root;
+    }
+    const BATCH: u64 = 128;
+    for c in 1u64.. {
+        let f = |x: u64| ((mod_mul(x, x, n) as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut ys) = (0u64, 2u64, 0u64);
+        let mut q = 1u64;
+        let mut g = 1u64;
+        let mut r = 1u64;
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = mod_mul(q, x.abs_diff(y), n);
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // The batch overshot; redo it one step at a time.
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!()
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn mod_add_u128(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b { a - (m - b) } else { a + b }
+}
+
+fn mod_sub_u128(a: u128, b: u128, m: u128) -> u128 {
+    if a >= b { a - b } else { m - (b - a) }
+}
+
+fn mod_mul_u128(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    let (mut a, mut b, mut res) = (a % m, b, 0u128);
+    while b > 0 {
+        if b & 1 == 1 {
+            res = mod_add_u128(res, a, m);
+        }
+        a = mod_add_u128(a, a, m);
+        b >>= 1;
+    }
+    res
+}
+
+fn mod_pow_u128(mut a: u128, mut e: u128, m: u128) -> u128 {
+    let mut res = 1u128 % m;
+    while e > 0 {
+        if e & 1 == 1 {
+            res = mod_mul_u128(res, a, m);
+        }
+        a = mod_mul_u128(a, a, m);
+        e >>= 1;
+    }
+    res
+}
+
+/// Jacobi symbol `(a / n)` for odd `n`.
+fn jacobi(mut a: u128, mut n: u128) -> i32 {
+    a %= n;
+    let mut sign = 1;
+    while a != 0 {
+        while a.is_multiple_of(2) {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                sign = -sign;
+            }
+        }
+        (a, n) = (n, a);
+        if a % 4 == 3 && n % 4 == 3 {
+            sign = -sign;
+        }
+        a %= n;
+    }
+    if n == 1 { sign } else { 0 }
+}
+
+/// Strong Lucas probable-prime test with Selfridge's parameters, for odd
+/// `n` that is not a perfect square.
+fn strong_lucas(n: u128) -> bool {
+    let mut d = 5i128;
+    loop {
+        let d_mod = if d < 0 { n - (d.unsigned_abs() % n) } else { d as u128 % n };
+        match jacobi(d_mod, n) {
+            -1 => break,
+            0 if d.unsigned_abs() != n => return false,
+            _ => d = if d > 0 { -(d + 2) } else { -d + 2 },
+        }
+    }
+    let d_mod = if d < 0 { n - (d.unsigned_abs() % n) } else { d as u128 % n };
+    let q = (1 - d) / 4;
+    let q_mod = if q < 0 { n - (q.unsigned_abs() % n) } else { q as u128 % n };
+    let half = |x: u128| if x.is_multiple_of(2) { x / 2 } else { x / 2 + n / 2 + 1 };
+
+    // n + 1 = k * 2^s with k odd. It cannot overflow: u128::MAX is a
+    // multiple of 3 and never gets here.
+    let mut k = n + 1;
+    let mut s = 0u32;
+    while k.is_multiple_of(2) {
+        k /= 2;
+        s += 1;
+    }
+
+    // Walk the bits of k from the top, with P = 1.
+    let (mut u, mut v, mut qk) = (1u128, 1u128, q_mod);
+    for bit in (0..127 - k.leading_zeros()).rev() {
+        u = mod_mul_u128(u, v, n);
+        v = mod_sub_u128(mod_mul_u128(v, v, n), mod_mul_u128(2, qk, n), n);
+        qk = mod_mul_u128(qk, qk, n);
+        if (k >> bit) & 1 == 1 {
+            let next_u = half(mod_add_u128(u, v, n));
+            let next_v = half(mod_add_u128(mod_mul_u128(d_mod, u, n), v, n));
+            u = next_u;
+            v = next_v;
+            qk = mod_mul_u128(qk, q_mod, n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = mod_sub_u128(mod_mul_u128(v, v, n), mod_mul_u128(2, qk, n), n);
+        qk = mod_mul_u128(qk, qk, n);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Primality test for `u128`. Below 3.3 * 10^24 it is Miller-Rabin over the
+/// first thirteen primes, which is exact there; above that it is the
+/// Baillie-PSW test, which has no known counterexample.
+pub fn is_prime_u128(n: u128) -> bool {
+    if let Ok(small) = u64::try_from(n) {
+        return is_prime_u64(small);
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p as u128) {
+            return false;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    let strong_probable_prime = |a: u128| {
+        let mut x = mod_pow_u128(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mod_mul_u128(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    };
+    if n < 3_317_044_064_679_887_385_961_981 {
+        return SMALL_PRIMES[..13].iter().all(|&a| strong_probable_prime(a as u128));
+    }
+    let root = n.isqrt();
+    strong_probable_prime(2) && root * root != n && strong_lucas(n)
+}
+
+/// Return the nth (0-based) permutation of the input string.
+/// Example: perm(3, "abc") -> "bac".
+pub fn perm(mut n: u64, s: &str) -> String {
+    let mut chars: Vec<char> = s.chars().collect();
+    let len = chars.len();
+    if len == 0 {
+        return String::new();
+    }
+
+    let mut fact = vec![1u64; len + 1];
+    for i in 1..=len {
+        fact[i] = fact[i - 1].saturating_mul(i as u64);
+    }
+    if fact[len] > 0 {
+        n %= fact[len];
+    }
+
+    let mut res = String::with_capacity(len);
+    for i in (1..=len).rev() {
+        let f = fact[i - 1];
+        let idx = (n / f) as usize;
+        n %= f;
+        let ch = chars.remove(idx);
+        res.push(ch);
+    }
+    res
+}
+
+/// Compute the binomial coefficient C(n, k).
+/// Example: binomial(5, 2) -> 10.
+pub fn binomial(n: u64, k: u64) -> u128 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut res: u128 = 1;
+    for i in 0..k {
+        res = res * (n - i) as u128 / (i + 1) as u128;
+    }
+    res
+}
+
+/// Compute the nth Catalan number.
+/// Example: catalan_number(4) -> 14.
+pub fn catalan_number(n: u64) -> u128 {
+    let mut num: u128 = 1;
+    let mut den: u128 = 1;
+    for k in 2..=n {
+        num *= (n + k) as u128;
+        den *= k as u128;
+    }
+    num / den
+}
+
+/// Uniform rank in `0..count`, for the `random_*` generators that unrank.
+fn random_rank(rng: &mut impl Rng, count: u128) -> u128 {
+    assert!(count > 0, "no objects to choose from");
+    rng.random_range(0..count)
+}
+
+/// `table[i][m]`: partitions of `i` into parts of size at most `m`.
+fn partition_table(n: usize) -> Vec<Vec<u128>> {
+    let mut table = vec![vec![0u128; n + 1]; n + 1];
+    table[0].fill(1);
+    for i in 1..=n {
+        for m in 1..=n {
+            let with_m = if m <= i { table[i - m][m] } else { 0 };
+            table[i][m] = table[i][m - 1]
+                .checked_add(with_m)
+                .expect("partition count overflows u128");
+        }
+    }
+    table
+}
+
+/// Number of integer partitions of `n`. Fits `u128` up to `n` in the
+/// low thousands. Example: partition_count(5) -> 7.
+pub fn partition_count(n: usize) -> u128 {
+    partition_table(n)[n][n]
+}
+
+/// Rank of a partition (parts in non-increasing order) among all
+/// partitions of its sum, in lexicographic order of the parts; `[1, 1, ...]`
+/// is 0 and `[n]` is last.
+pub fn partition_rank(parts: &[usize]) -> u128 {
+    assert!(parts.windows(2).all(|w| w[0] >= w[1]), "parts must be non-increasing");
+    assert!(parts.iter().all(|&p| p > 0), "parts must be positive");
+    let mut rem: usize = parts.iter().sum();
+    let table = partition_table(rem);
+    let mut rank = 0u128;
+    for &a in parts {
+        rank += (1..a).map(|b| table[rem - b][b]).sum::<u128>();
+        rem -= a;
+    }
+    rank
+}
+
+/// Partition of `n` with the given rank, inverse of `partition_rank`.
+/// Example: partition_unrank(4, 3) -> [3, 1].
+pub fn partition_unrank(n: usize, mut rank: u128) -> Vec<usize> {
+    let table = partition_table(n);
+    assert!(rank < table[n][n], "rank out of range");
+    let (mut parts, mut rem, mut bound) = (Vec::new(), n, n);
+    while rem > 0 {
+        for a in 1..=bound.min(rem) {
+            let count = table[rem - a][a];
+            if rank < count {
+                parts.push(a);
+                rem -= a;
+                bound = a;
+                break;
+            }
+            rank -= count;
+        }
+    }
+    parts
+}
+
+/// Uniformly random partition of `n`, parts in non-increasing order.
+pub fn random_partition(rng: &mut impl Rng, n: usize) -> Vec<usize> {
+    let rank = random_rank(rng, partition_count(n));
+    partition_unrank(n, rank)
+}
+
+/// Number of compositions (ordered partitions) of `n`: `2^(n-1)`, and 1 for
+/// the empty composition of 0. Requires `n <= 128`.
+pub fn composition_count(n: usize) -> u128 {
+    assert!(n <= 128, "composition count overflows u128");
+    if n == 0 { 1 } else { 1u128 << (n - 1) }
+}
+
+/// Rank of a composition among those of its sum, in lexicographic order;
+/// `[1, 1, ...]` is 0 and `[n]` is last.
+pub fn composition_rank(parts: &[usize]) -> u128 {
+    assert!(parts.iter().all(|&p| p > 0), "parts must be positive");
+    let mut rem: usize = parts.iter().sum();
+    let mut rank = 0u128;
+    for &a in parts {
+        rank += (1..a).map(|b| composition_count(rem - b)).sum::<u128>();
+        rem -= a;
+    }
+    rank
+}
+
+/// Composition of `n` with the given rank, inverse of `composition_rank`.
+pub fn composition_unrank(n: usize, mut rank: u128) -> Vec<usize> {
+    assert!(rank < composition_count(n), "rank out of range");
+    let (mut parts, mut rem) = (Vec::new(), n);
+    while rem > 0 {
+        for a in 1..=rem {
+            let count = composition_count(rem - a);
+            if rank < count {
+                parts.push(a);
+                rem -= a;
+                break;
+            }
+            rank -= count;
+        }
+    }
+    parts
+}
+
+/// Uniformly random composition of `n`: each of the `n - 1` gaps between
+/// units is a cut with probability 1/2. Works for any `n`.
+pub fn random_composition(rng: &mut impl Rng, n: usize) -> Vec<usize> {
+    let mut parts = Vec::new();
+    let mut current = 0;
+    for i in 0..n {
+        current += 1;
+        if i + 1 == n || rng.random_bool(0.5) {
+            parts.push(current);
+            current = 0;
+        }
+    }
+    parts
+}
+
+/// Uniformly random composition of `n` into exactly `k` positive parts,
+/// e.g. to split a total over `k` test cases.
+/// Example: random_composition_k(rng, 10, 3) -> [2, 5, 3].
+pub fn random_composition_k(rng: &mut impl Rng, n: usize, k: usize) -> Vec<usize> {
+    assert!((k > 0 && k <= n) || (n == 0 && k == 0), "need 1 <= k <= n");
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut cuts = rand::seq::index::sample(rng, n - 1, k - 1).into_vec();
+    cuts.sort_unstable();
+    let mut parts = Vec::with_capacity(k);
+    let mut prev = 0;
+    for cut in cuts.into_iter().map(|c| c + 1).chain([n]) {
+        parts.push(cut - prev);
+        prev = cut;
+    }
+    parts
+}
+
+/// Rank of a sorted `k`-subset of `0..n` in lexicographic order; there are
+/// `binomial(n, k)` of them.
+pub fn k_subset_rank(subset: &[usize], n: usize) -> u128 {
+    assert!(subset.windows(2).all(|w| w[0] < w[1]), "subset must be strictly increasing");
+    let k = subset.len();
+    let mut rank = 0u128;
+    let mut start = 0;
+    for (j, &x) in subset.iter().enumerate() {
+        assert!(x < n, "subset element out of range");
+        rank += (start..x)
+            .map(|v| binomial((n - 1 - v) as u64, (k - 1 - j) as u64))
+            .sum::<u128>();
+        start = x + 1;
+    }
+    rank
+}
+
+/// Sorted `k`-subset of `0..n` with the given rank, inverse of `k_subset_rank`.
+/// Example: k_subset_unrank(4, 2, 3) -> [1, 2].
+pub fn k_subset_unrank(n: usize, k: usize, mut rank: u128) -> Vec<usize> {
+    assert!(rank < binomial(n as u64, k as u64), "rank out of range");
+    let mut subset = Vec::with_capacity(k);
+    let mut v = 0;
+    for j in 0..k {
+        loop {
+            let count = binomial((n - 1 - v) as u64, (k - 1 - j) as u64);
+            v += 1;
+            if rank < count {
+                subset.push(v - 1);
+                break;
+            }
+            rank -= count;
+        }
+    }
+    subset
+}
+
+/// Uniformly random sorted `k`-subset of `0..n`. Works for any `n`.
+pub fn random_k_subset(rng: &mut impl Rng, n: usize, k: usize) -> Vec<usize> {
+    assert!(k <= n, "k cannot exceed n");
+    let mut subset = rand::seq::index::sample(rng, n, k).into_vec();
+    subset.sort_unstable();
+    subset
+}
+
+/// `table[i][m]`: ways to finish a restricted growth string from position
+/// `i` of `n` when `m` blocks are open.
+fn set_partition_table(n: usize) -> Vec<Vec<u128>> {
+    let mut table = vec![vec![0u128; n + 2]; n + 1];
+    table[n].fill(1);
+    // At most `i` blocks can be open before position `i`.
+    for i in (0..n).rev() {
+        for m in 0..=i {
+            table[i][m] = (m as u128)
+                .checked_mul(table[i + 1][m])
+                .and_then(|x| x.checked_add(table[i + 1][m + 1]))
+                .expect("Bell number overflows u128");
+        }
+    }
+    table
+}
+
+/// Bell number: the number of set partitions of `n` elements. Fits `u128`
+/// up to `n = 41`. Example: bell_number(4) -> 15.
+pub fn bell_number(n: usize) -> u128 {
+    set_partition_table(n)[0][0]
+}
+
+/// Rank of a set partition of `0..n`, given as a restricted growth string
+/// (`blocks[i]` is the block of element `i`, blocks numbered in order of
+/// first appearance), in lexicographic order of that string.
+pub fn set_partition_rank(blocks: &[usize]) -> u128 {
+    let table = set_partition_table(blocks.len());
+    let (mut rank, mut open) = (0u128, 0);
+    for (i, &b) in blocks.iter().enumerate() {
+        assert!(b <= open, "not a restricted growth string");
+        rank += b as u128 * table[i + 1][open];
+        open = open.max(b + 1);
+    }
+    rank
+}
+
+/// Set partition of `0..n` with the given rank, as a restricted growth string.
+/// Example: set_partition_unrank(3, 1) -> [0, 0, 1].
+pub fn set_partition_unrank(n: usize, mut rank: u128) -> Vec<usize> {
+    let table = set_partition_table(n);
+    assert!(rank < table[0][0], "rank out of range");
+    let (mut blocks, mut open) = (Vec::with_capacity(n), 0);
+    for i in 0..n {
+        let per_block = table[i + 1][open];
+        let b = (rank / per_block).min(open as u128);
+        rank -= b * per_block;
+        blocks.push(b as usize);
+        open = open.max(b as usize + 1);
+    }
+    blocks
+}
+
+/// Uniformly random set partition of `0..n` as a restricted growth string.
+pub fn random_set_partition(rng: &mut impl Rng, n: usize) -> Vec<usize> {
+    let rank = random_rank(rng, bell_number(n));
+    set_partition_unrank(n, rank)
+}
+
+/// `table[r][h]`: ways to finish a Dyck path with `r` steps left at height `h`.
+fn dyck_table(n: usize) -> Vec<Vec<u128>> {
+    let mut table = vec![vec![0u128; n + 2]; 2 * n + 1];
+    table[0][0] = 1;
+    for r in 1..=2 * n {
+        for h in 0..=n {
+            let down = if h > 0 { table[r - 1][h - 1] } else { 0 };
+            table[r][h] = table[r - 1][h + 1]
+                .checked_add(down)
+                .expect("Dyck path count overflows u128");
+        }
+    }
+    table
+}
+
+/// Rank of a balanced parenthesis string in lexicographic order (`(`
+/// before `)`); there are `catalan_number(n)` of semilength `n`.
+pub fn dyck_path_rank(path: &str) -> u128 {
+    let steps = path.as_bytes();
+    assert!(steps.len().is_multiple_of(2), "path must have even length");
+    let table = dyck_table(steps.len() / 2);
+    let (mut rank, mut h) = (0u128, 0usize);
+    for (i, &c) in steps.iter().enumerate() {
+        let left = steps.len() - i - 1;
+        match c {
+            b'(' => h += 1,
+            b')' => {
+                rank += table[left][h + 1];
+                h = h.checked_sub(1).expect("path goes below zero");
+            }
+            _ => panic!("path must consist of '(' and ')'"),
+        }
+    }
+    assert_eq!(h, 0, "path must end at height zero");
+    rank
+}
+
+/// Dyck path of semilength `n` with the given rank, inverse of `dyck_path_rank`.
+/// Example: dyck_path_unrank(2, 1) -> "()()".
+pub fn dyck_path_unrank(n: usize, mut rank: u128) -> String {
+    let table = dyck_table(n);
+    assert!(rank < table[2 * n][0], "rank out of range");
+    let (mut path, mut h) = (String::with_capacity(2 * n), 0usize);
+    for i in 0..2 * n {
+        let up = table[2 * n - i - 1][h + 1];
+        if rank < up {
+            path.push('(');
+            h += 1;
+        } else {
+            rank -= up;
+            path.push(')');
+            h -= 1;
+        }
+    }
+    path
+}
+
+/// Uniformly random Dyck path of semilength `n` by the cycle lemma: of the
+/// rotations of a shuffled `n` ups and `n + 1` downs, exactly one stays
+/// non-negative before its final down. Works for any `n`.
+pub fn random_dyck_path(rng: &mut impl Rng, n: usize) -> String {
+    let mut steps: Vec<bool> = (0..2 * n + 1).map(|i| i < n).collect();
+    steps.shuffle(rng);
+    // Rotate to just after the first minimum of the prefix sums.
+    let (mut h, mut low, mut start) = (0i64, 0i64, 0);
+    for (i, &up) in steps.iter().enumerate() {
+        h += if up { 1 } else { -1 };
+        if h < low {
+            low = h;
+            start = i + 1;
+        }
+    }
+    steps.rotate_left(start);
+    steps[..2 * n].iter().map(|&up| if up { '(' } else { ')' }).collect()
+}
+
+/// `table[r][k]`: arrangements of `r` values in `r` positions where `k` of
+/// the values each avoid one distinct position.
+fn derangement_table(n: usize) -> Vec<Vec<u128>> {
+    let mut table = vec![vec![0u128; n + 1]; n + 1];
+    let mut fact = 1u128;
+    for r in 0..=n {
+        if r > 0 {
+            fact = fact.checked_mul(r as u128).expect("derangement count overflows u128");
+        }
+        table[r][0] = fact;
+        for k in 1..=r {
+            table[r][k] = table[r][k - 1] - table[r - 1][k - 1];
+        }
+    }
+    table
+}
+
+/// Number of derangements of `n` elements (permutations without fixed
+/// points), `!n`. Requires `n <= 34`. Example: derangement_count(4) -> 9.
+pub fn derangement_count(n: usize) -> u128 {
+    derangement_table(n)[n][n]
+}
+
+/// Arrangements left after fixing `p[..=i]` from `remaining` values.
+fn derangement_completions(table: &[Vec<u128>], remaining: &[usize], i: usize) -> u128 {
+    let n = table.len() - 1;
+    let constrained = remaining.iter().filter(|&&u| u > i).count();
+    table[n - i - 1][constrained]
+}
+
+/// Rank of a derangement of `0..n` in lexicographic order.
+pub fn derangement_rank(p: &[usize]) -> u128 {
+    let table = derangement_table(p.len());
+    let mut remaining: Vec<usize> = (0..p.len()).collect();
+    let mut rank = 0u128;
+    for (i, &x) in p.iter().enumerate() {
+        assert!(x != i, "not a derangement");
+        let pos = remaining.binary_search(&x).expect("not a permutation");
+        for idx in 0..pos {
+            if remaining[idx] != i {
+                let v = remaining.remove(idx);
+                rank += derangement_completions(&table, &remaining, i);
+                remaining.insert(idx, v);
+            }
+        }
+        remaining.remove(pos);
+    }
+    rank
+}
+
+/// Derangement of `0..n` with the given rank, inverse of `derangement_rank`.
+/// Example: derangement_unrank(3, 0) -> [1, 2, 0].
+pub fn derangement_unrank(n: usize, mut rank: u128) -> Vec<usize> {
+    let table = derangement_table(n);
+    assert!(rank < table[n][n], "rank out of range");
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut p = Vec::with_capacity(n);
+    for i in 0..n {
+        for idx in 0..remaining.len() {
+            if remaining[idx] == i {
+                continue;
+            }
+            let v = remaining.remove(idx);
+            let count = derangement_completions(&table, &remaining, i);
+            if rank < count {
+                p.push(v);
+                break;
+            }
+            rank -= count;
+            remaining.insert(idx, v);
+        }
+    }
+    p
+}
+
+/// Uniformly random derangement of `0..n` by rejecting permutations with a
+/// fixed point (about `e` tries on average). Works for any `n != 1`.
+pub fn random_derangement(rng: &mut impl Rng, n: usize) -> Vec<usize> {
+    assert!(n != 1, "a single element has no derangement");
+    let mut p: Vec<usize> = (0..n).collect();
+    loop {
+        p.shuffle(rng);
+        if p.iter().enumerate().all(|(i, &x)| i != x) {
+            return p;
+        }
+    }
+}
+
+/// C(n, k) without overflow.
+/// Example: binomial_big(100, 50) -> 100891344545564193334812497256.
+#[cfg(feature = "bigint")]
+pub fn binomial_big(n: u64, k: u64) -> BigUint {
+    if k > n {
+        return BigUint::zero();
+    }
+    let k = k.min(n - k);
+    let mut res = BigUint::one();
+    for i in 0..k {
+        // The product of i + 1 consecutive numbers is a multiple of (i + 1)!.
+        res = res.mul_small(n - i).div_rem_small(i + 1).0;
+    }
+    res
+}
+
+/// The nth Catalan number without overflow.
+#[cfg(feature = "bigint")]
+pub fn catalan_number_big(n: u64) -> BigUint {
+    binomial_big(2 * n, n).div_rem_small(n + 1).0
+}
+
+/// The nth Fibonacci number without overflow, by fast doubling.
+#[cfg(feature = "bigint")]
+pub fn fibonacci_big(n: u64) -> BigUint {
+    // (F(k), F(k + 1)), walking the bits of n from the top.
+    let mut a = BigUint::zero();
+    let mut b = BigUint::one();
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        let c = &a * &(&b.mul_small(2) - &a);
+        let d = &(&a * &a) + &(&b * &b);
+        if (n >> bit) & 1 == 1 {
+            b = &c + &d;
+            a = d;
+        } else {
+            a = c;
+            b = d;
+        }
+    }
+    a
+}
+
+/// Convert a big number to the given base (2..=62), like `dec2base`.
+#[cfg(feature = "bigint")]
+pub fn dec2base_big(n: &BigUint, base: u32) -> String {
+    if !(2..=62).contains(&base) {
+        return String::new();
+    }
+    n.to_str_radix(base)
+}
+
+/// Generate primes in [2, n) with a simple sieve.
+/// Example: prime_sieve(10) -> [2, 3, 5, 7].
+pub fn prime_sieve(n: u64) -> Vec<u64> {
+    if n <= 2 {
+        return Vec::new();
+    }
+
+    let size = (n / 2) as usize;
+    let mut sieve = vec![true; size];
+    let mut i = 3u64;
+    while i * i < n {
+        if sieve[(i / 2) as usize] {
+            let mut j = i * i;
+            let step = i * 2;
+            while j < n {
+                sieve[(j / 2) as usize] = false;
+                j += step;
+            }
+        }
+        i += 2;
+    }
+
+    let mut res = Vec::new();
+    res.push(2);
+    let mut i = 3u64;
+    while i < n {
+        if sieve[(i / 2) as usize] {
+            res.push(i);
+        }
+        i += 2;
+    }
+    res
+}
+
+/// Draw a value from `lo..=hi` satisfying `pred`: random probes first, then
+/// a scan from a random start that wraps around, so sparse ranges still
+/// finish. Returns `None` when nothing in the range qualifies.
+fn random_matching(
+    rng: &mut impl Rng,
+    lo: u64,
+    hi: u64,
+    tries: usize,
+    pred: impl Fn(u64) -> bool,
+) -> Option<u64> {
+    if lo > hi {
+        return None;
+    }
+    for _ in 0..tries {
+        let x = rng.random_range(lo..=hi);
+        if pred(x) {
+            return Some(x);
+        }
+    }
+    let start = rng.random_range(lo..=hi);
+    (start..=hi).chain(lo..start).find(|&x| pred(x))
+}
+
+/// Random prime in `[lo, hi]`, or `None` if the range holds no prime.
+/// Pass a seeded generator such as `SeededRng::rng()` for reproducible data.
+pub fn random_prime(rng: &mut impl Rng, lo: u64, hi: u64) -> Option<u64> {
+    random_matching(rng, lo.max(2), hi, 256, |x| miller_rabin(x, 0))
+}
+
+/// Random prime with exactly `bits` bits (2..=64).
+/// Example: random_prime_bits(rng, 4) is 11 or 13.
+pub fn random_prime_bits(rng: &mut impl Rng, bits: u32) -> u64 {
+    assert!((2..=64).contains(&bits), "bits must be between 2 and 64");
+    let lo = 1u64 << (bits - 1);
+    let hi = if bits == 64 { u64::MAX } else { (1u64 << bits) - 1 };
+    random_prime(rng, lo, hi).expect("Bertrand's postulate guarantees a prime")
+}
+
+/// Random safe prime `p = 2q + 1` (with `q` prime) in `[lo, hi]`.
+pub fn random_safe_prime(rng: &mut impl Rng, lo: u64, hi: u64) -> Option<u64> {
+    if hi < 5 {
+        return None;
+    }
+    let q_lo = lo.saturating_sub(1).div_ceil(2).max(2);
+    let q_hi = (hi - 1) / 2;
+    random_matching(rng, q_lo, q_hi, 4096, |q| miller_rabin(q, 0) && miller_rabin(2 * q + 1, 0))
+        .map(|q| 2 * q + 1)
+}
+
+/// Random semiprime `n = p * q <= limit` with primes `p <= q`, returned as
+/// `(n, p, q)`. With `max_gap`, `p` sits just below `sqrt(limit)` and
+/// `q - p <= max_gap`, the hard case for Fermat-style factoring.
+pub fn random_semiprime(rng: &mut impl Rng, limit: u64, max_gap: Option<u64>) -> (u64, u64, u64) {
+    assert!(limit >= 4, "limit must be at least 4");
+    let root = limit.isqrt();
+    let (p, q) = match max_gap {
+        None => {
+            let p = random_prime(rng, 2, root).unwrap();
+            let q = random_prime(rng, p, limit / p).unwrap_or(p);
+            (p, q)
+        }
+        Some(gap) => {
+            let p = random_prime(rng, root.saturating_sub(gap).max(2), root)
+                .or_else(|| random_prime(rng, 2, root))
+                .unwrap();
+            let q = random_prime(rng, p, p.saturating_add(gap).min(limit / p)).unwrap_or(p);
+            (p, q)
+        }
+    };
+    (p * q, p, q)
+}
+
+const SMALL_PRIMES: [u64; 16] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+
+/// All `n <= limit` of the form `2^a * 3^b * 5^c * ...` with non-increasing
+/// exponents, paired with their divisor counts. Every divisor count reachable
+/// below `limit` is reached first by such a number.
+fn divisor_rich_numbers(limit: u64) -> Vec<(u64, u64)> {
+    let mut res = Vec::new();
+    let mut stack = vec![(1u64, 1u64, 0usize, u32::MAX)];
+    while let Some((n, divisors, idx, max_exp)) = stack.pop() {
+        res.push((n, divisors));
+        if idx == SMALL_PRIMES.len() {
+            continue;
+        }
+        let p = SMALL_PRIMES[idx];
+        let mut m = n;
+        let mut e = 0u32;
+        while e < max_exp {
+            m = match m.checked_mul(p) {
+                Some(m) if m <= limit => m,
+                _ => break,
+            };
+            e += 1;
+            stack.push((m, divisors * (e as u64 + 1), idx + 1, e));
+        }
+    }
+    res
+}
+
+/// Highly composite numbers up to `limit` with their divisor counts: each
+/// has more divisors than any smaller positive number.
+/// Example: highly_composite(12) -> [(1, 1), (2, 2), (4, 3), (6, 4), (12, 6)].
+pub fn highly_composite(limit: u64) -> Vec<(u64, u64)> {
+    let mut candidates = divisor_rich_numbers(limit);
+    candidates.sort_unstable();
+    let mut best = 0;
+    candidates.retain(|&(_, divisors)| {
+        let record = divisors > best;
+        best = best.max(divisors);
+        record
+    });
+    candidates
+}
+
+/// Random number `<= limit` among the `top` ones with the most divisors
+/// (drawn from the numbers with non-increasing prime exponents), returned
+/// with its divisor count.
+pub fn random_many_divisors(rng: &mut impl Rng, limit: u64, top: usize) -> (u64, u64) {
+    assert!(limit > 0, "limit must be above zero");
+    assert!(top > 0, "top must be above zero");
+    let mut candidates = divisor_rich_numbers(limit);
+    candidates.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    candidates[rng.random_range(0..top.min(candidates.len()))]
+}
+
+/// Korselt's criterion: `n` is an odd squarefree composite and `p - 1`
+/// divides `n - 1` for every prime `p | n`.
+pub fn is_carmichael(n: u64) -> bool {
+    if n < 3 || n.is_multiple_of(2) {
+        return false;
+    }
+    let factors = factor(n);
+    factors.len() >= 2 && factors.iter().all(|&(p, e)| e == 1 && (n - 1).is_multiple_of(p - 1))
+}
+
+/// Random Carmichael number `<= limit` of Chernick's form
+/// `(6k + 1)(12k + 1)(18k + 1)` with all three factors prime, or `None` if
+/// `limit < 1729`.
+pub fn random_carmichael(rng: &mut impl Rng, limit: u64) -> Option<u64> {
+    let mut found = Vec::new();
+    for k in 1u64.. {
+        let (a, b, c) = (6 * k + 1, 12 * k + 1, 18 * k + 1);
+        let n = a as u128 * b as u128 * c as u128;
+        if n > limit as u128 {
+            break;
+        }
+        if miller_rabin(a, 0) && miller_rabin(b, 0) && miller_rabin(c, 0) {
+            found.push(n as u64);
+        }
+    }
+    if found.is_empty() {
+        None
+    } else {
+        Some(found[rng.random_range(0..found.len())])
+    }
+}
+
+/// Random `bound`-smooth number `<= limit`: a product of random primes up to
+/// `bound`, grown until no such prime fits any more.
+pub fn random_smooth(rng: &mut impl Rng, limit: u64, bound: u64) -> u64 {
+    assert!(limit > 0, "limit must be above zero");
+    assert!(bound >= 2, "bound must be at least 2");
+    let primes = prime_sieve(bound + 1);
+    let mut n = 1u64;
+    loop {
+        let room = limit / n;
+        let usable = primes.partition_point(|&p| p <= room);
+        if usable == 0 {
+            return n;
+        }
+        n *= primes[rng.random_range(0..usable)];
+    }
+}
+
+/// Tables from `linear_sieve`, indexed by `0..=n`; entries for 0 and 1 follow
+/// the usual conventions (`spf` is 0 there, `phi[1] = mu[1] = 1`).
+pub struct LinearSieve {
+    pub primes: Vec<u64>,
+    /// Smallest prime factor.
+    pub spf: Vec<u32>,
+    pub phi: Vec<u32>,
+    pub mu: Vec<i8>,
+    pub divisor_count: Vec<u32>,
+    pub divisor_sum: Vec<u64>,
+}
+
+impl LinearSieve {
+    /// Factorization of `x <= n` by following smallest prime factors.
+    pub fn factor(&self, mut x: usize) -> Vec<(u64, u32)> {
+        let mut res: Vec<(u64, u32)> = Vec::new();
+        while x > 1 {
+            let p = self.spf[x] as usize;
+            match res.last_mut() {
+                Some((q, e)) if *q == p as u64 => *e += 1,
+                _ => res.push((p as u64, 1)),
+            }
+            x /= p;
+        }
+        res
+    }
+}
+
+/// Linear (Euler) sieve up to `n`: every composite is crossed out once, by
+/// its smallest prime factor, which also yields phi, mu, the divisor count
+/// and the divisor sum of every number in the same pass.
+pub fn linear_sieve(n: usize) -> LinearSieve {
+    assert!(n < u32::MAX as usize, "n must fit in u32");
+    let mut sieve = LinearSieve {
+        primes: Vec::new(),
+        spf: vec![0; n + 1],
+        phi: vec![0; n + 1],
+        mu: vec![0; n + 1],
+        divisor_count: vec![0; n + 1],
+        divisor_sum: vec![0; n + 1],
+    };
+    if n >= 1 {
+        sieve.phi[1] = 1;
+        sieve.mu[1] = 1;
+        sieve.divisor_count[1] = 1;
+        sieve.divisor_sum[1] = 1;
+    }
+    // Exponent of the smallest prime, and 1 + p + ... + p^e for it.
+    let mut exp = vec![0u32; n + 1];
+    let mut power_sum = vec![0u64; n + 1];
+    for i in 2..=n {
+        if sieve.spf[i] == 0 {
+            sieve.spf[i] = i as u32;
+            sieve.primes.push(i as u64);
+            sieve.phi[i] = i as u32 - 1;
+            sieve.mu[i] = -1;
+            sieve.divisor_count[i] = 2;
+            sieve.divisor_sum[i] = i as u64 + 1;
+            exp[i] = 1;
+            power_sum[i] = i as u64 + 1;
+        }
+        for &p in &sieve.primes {
+            let j = i * p as usize;
+            if p > sieve.spf[i] as u64 || j > n {
+                break;
+            }
+            sieve.spf[j] = p as u32;
+            if p == sieve.spf[i] as u64 {
+                sieve.phi[j] = sieve.phi[i] * p as u32;
+                sieve.mu[j] = 0;
+                exp[j] = exp[i] + 1;
+                sieve.divisor_count[j] = sieve.divisor_count[i] / (exp[i] + 1) * (exp[j] + 1);
+                power_sum[j] = power_sum[i] * p + 1;
+                sieve.divisor_sum[j] = sieve.divisor_sum[i] / power_sum[i] * power_sum[j];
+                break;
+            }
+            sieve.phi[j] = sieve.phi[i] * (p as u32 - 1);
+            sieve.mu[j] = -sieve.mu[i];
+            exp[j] = 1;
+            sieve.divisor_count[j] = sieve.divisor_count[i] * 2;
+            power_sum[j] = p + 1;
+            sieve.divisor_sum[j] = sieve.divisor_sum[i] * (p + 1);
+        }
+    }
+    sieve
+}
+
+/// Primes in `[lo, hi]`, crossing out multiples of the primes up to
+/// `sqrt(hi)` in a window of `hi - lo + 1` flags. Suits ranges such as
+/// `[10^12, 10^12 + 10^6]`.
+pub fn segmented_sieve(lo: u64, hi: u64) -> Vec<u64> {
+    let lo = lo.max(2);
+    if lo > hi {
+        return Vec::new();
+    }
+    let len = (hi - lo + 1) as usize;
+    let mut composite = vec![false; len];
+    for p in prime_sieve(hi.isqrt() + 1) {
+        let mut m = match lo.div_ceil(p).checked_mul(p) {
+            Some(first) => first.max(p * p),
+            None => continue,
+        };
+        while m <= hi {
+            composite[(m - lo) as usize] = true;
+            m = match m.checked_add(p) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+    composite
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| !c)
+        .map(|(i, _)| lo + i as u64)
+        .collect()
+}
+
+/// Extended Euclidean algorithm.
+/// Returns (x, y, gcd) such that a*x + b*y = gcd.
+pub fn exgcd(mut a: i64, mut b: i64) -> (i64, i64, i64) {
+    let (mut u, mut v, mut s, mut t) = (1i64, 0i64, 0i64, 1i64);
+    while b != 0 {
+        let q = a / b;
+        let r = a % b;
+        a = b;
+        b = r;
+        let next_u = u - q * s;
+        let next_v = v - q * t;
+        u = s;
+        v = t;
+        s = next_u;
+        t = next_v;
+    }
+    (u, v, a)
+}
+
+/// Modular inverse of a modulo m, if it exists.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m == 0 {
+        return None;
+    }
+    let (x, _, g) = exgcd(a, m);
+    if g != 1 && g != -1 {
+        return None;
+    }
+    let mut res = x % m;
+    if res < 0 {
+        res += m.abs();
+    }
+    Some(res)
+}
+
+/// Inverse of `a` modulo `m` for the full `u64` range, if it exists.
+fn inverse_mod_u64(a: u64, m: u64) -> Option<u64> {
+    let (mut old_r, mut r) = (a as i128 % m as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    if old_r != 1 && m != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(m as i128) as u64)
+}
+
+/// Integer modulo the compile-time modulus `M`, kept in `[0, M)`.
+/// Example: ModInt::<7>::new(5) * ModInt::new(4) == ModInt::new(6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModInt<const M: u64> {
+    value: u64,
+}
+
+pub type ModInt998244353 = ModInt<998_244_353>;
+pub type ModInt1000000007 = ModInt<1_000_000_007>;
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: u64) -> Self {
+        Self { value: value % M }
+    }
+
+    pub fn modulus() -> u64 {
+        M
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn pow(self, mut e: u64) -> Self {
+        let mut base = self;
+        let mut res = Self::new(1);
+        while e > 0 {
+            if e & 1 == 1 {
+                res *= base;
+            }
+            base *= base;
+            e >>= 1;
+        }
+        res
+    }
+
+    /// Multiplicative inverse, or `None` when `value` and `M` share a factor.
+    pub fn inv(self) -> Option<Self> {
+        inverse_mod_u64(self.value, M).map(|value| Self { value })
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        Self { value: (value as i128).rem_euclid(M as i128) as u64 }
+    }
+}
+
+// Untyped literals such as `ModInt::from(5)` fall back to `i32`.
+impl<const M: u64> From<i32> for ModInt<M> {
+    fn from(value: i32) -> Self {
+        Self::from(value as i64)
+    }
+}
+
+impl<const M: u64> From<u32> for ModInt<M> {
+    fn from(value: u32) -> Self {
+        Self::new(value as u64)
+    }
+}
+
+impl<const M: u64> std::fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<const M: u64> std::ops::Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (sum, overflow) = self.value.overflowing_add(other.value);
+        Self { value: if overflow || sum >= M { sum.wrapping_sub(M) } else { sum } }
+    }
+}
+
+impl<const M: u64> std::ops::Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        if self.value >= other.value {
+            Self { value: self.value - other.value }
+        } else {
+            Self { value: M - (other.value - self.value) }
+        }
+    }
+}
+
+impl<const M: u64> std::ops::Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self { value: mod_mul(self.value, other.value, M) }
+    }
+}
+
+impl<const M: u64> std::ops::Div for ModInt<M> {
+    type Output = Self;
+
+    /// Panics when `other` has no inverse modulo `M`.
+    fn div(self, other: Self) -> Self {
+        let inverse = other.inv().expect("divisor is not invertible modulo M");
+        Self { value: mod_mul(self.value, inverse.value, M) }
+    }
+}
+
+impl<const M: u64> std::ops::Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(0) - self
+    }
+}
+
+impl<const M: u64> std::ops::AddAssign for ModInt<M> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<const M: u64> std::ops::SubAssign for ModInt<M> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<const M: u64> std::ops::MulAssign for ModInt<M> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl<const M: u64> std::ops::DivAssign for ModInt<M> {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+/// Factorials and inverse factorials modulo a prime `M`, for `0..=n` with
+/// `n < M`.
+pub struct FactorialTable<const M: u64> {
+    fact: Vec<ModInt<M>>,
+    inv_fact: Vec<ModInt<M>>,
+}
+
+impl<const M: u64> FactorialTable<M> {
+    pub fn new(n: usize) -> Self {
+        assert!((n as u64) < M, "table size must stay below the modulus");
+        let mut fact = vec![ModInt::new(1); n + 1];
+        for i in 1..=n {
+            fact[i] = fact[i - 1] * ModInt::new(i as u64);
+        }
+        let mut inv_fact = vec![ModInt::new(1); n + 1];
+        inv_fact[n] = fact[n].inv().expect("modulus must be prime");
+        for i in (1..=n).rev() {
+            inv_fact[i - 1] = inv_fact[i] * ModInt::new(i as u64);
+        }
+        Self { fact, inv_fact }
+    }
+
+    pub fn fact(&self, n: usize) -> ModInt<M> {
+        self.fact[n]
+    }
+
+    pub fn inv_fact(&self, n: usize) -> ModInt<M> {
+        self.inv_fact[n]
+    }
+
+    /// C(n, k), zero when `k > n`.
+    pub fn binomial(&self, n: usize, k: usize) -> ModInt<M> {
+        if k > n {
+            return ModInt::new(0);
+        }
+        self.fact[n] * self.inv_fact[k] * self.inv_fact[n - k]
+    }
+
+    /// Ordered selections P(n, k) = n! / (n - k)!.
+    pub fn permutation(&self, n: usize, k: usize) -> ModInt<M> {
+        if k > n {
+            return ModInt::new(0);
+        }
+        self.fact[n] * self.inv_fact[n - k]
+    }
+
+    pub fn catalan(&self, n: usize) -> ModInt<M> {
+        self.binomial(2 * n, n) * self.fact[n] * self.inv_fact[n + 1]
+    }
+}
+
+/// C(n, k) mod a prime `p`, digit by digit in base `p` by Lucas' theorem, so
+/// `n` may exceed `p`. Each digit costs O(min(k_i, p)).
+/// Example: binomial_mod(10, 3, 7) -> 1.
+pub fn binomial_mod(mut n: u64, mut k: u64, p: u64) -> u64 {
+    assert!(p >= 2, "modulus must be a prime");
+    if k > n {
+        return 0;
+    }
+    let mut res = 1 % p;
+    while k > 0 && res != 0 {
+        let (ni, ki) = (n % p, k % p);
+        if ki > ni {
+            return 0;
+        }
+        let ki = ki.min(ni - ki);
+        let (mut num, mut den) = (1 % p, 1 % p);
+        for i in 0..ki {
+            num = mod_mul(num, ni - i, p);
+            den = mod_mul(den, i + 1, p);
+        }
+        let den_inv = inverse_mod_u64(den, p).expect("modulus must be a prime");
+        res = mod_mul(res, mod_mul(num, den_inv, p), p);
+        n /= p;
+        k /= p;
+    }
+    res
+}
+
+/// Lucas' theorem with factorials mod a small prime `p` precomputed, for
+/// many C(n, k) mod p queries in O(log_p n) each.
+pub struct Lucas {
+    p: u64,
+    fact: Vec<u64>,
+    inv_fact: Vec<u64>,
+}
+
+impl Lucas {
+    pub fn new(p: u64) -> Self {
+        assert!(is_prime(p), "modulus must be a prime");
+        let size = p as usize;
+        let mut fact = vec![1u64; size];
+        for i in 1..size {
+            fact[i] = mod_mul(fact[i - 1], i as u64, p);
+        }
+        let mut inv_fact = vec![1u64; size];
+        inv_fact[size - 1] = inverse_mod_u64(fact[size - 1], p).unwrap();
+        for i in (1..size).rev() {
+            inv_fact[i - 1] = mod_mul(inv_fact[i], i as u64, p);
+        }
+        Self { p, fact, inv_fact }
+    }
+
+    pub fn binomial(&self, mut n: u64, mut k: u64) -> u64 {
+        if k > n {
+            return 0;
+        }
+        let p = self.p;
+        let mut res = 1 % p;
+        while k > 0 {
+            let (ni, ki) = ((n % p) as usize, (k % p) as usize);
+            if ki > ni {
+                return 0;
+            }
+            res = mod_mul(res, self.fact[ni], p);
+            res = mod_mul(res, mod_mul(self.inv_fact[ki], self.inv_fact[ni - ki], p), p);
+            n /= p;
+            k /= p;
+        }
+        res
+    }
+}
+
+/// Smallest primitive root modulo the prime `p`.
+/// Example: primitive_root(998244353) -> 3.
+pub fn primitive_root(p: u64) -> u64 {
+    assert!(is_prime(p), "modulus must be a prime");
+    if p == 2 {
+        return 1;
+    }
+    let factors = factor(p - 1);
+    (2..p)
+        .find(|&g| factors.iter().all(|&(q, _)| mod_pow(g, (p - 1) / q, p) != 1))
+        .unwrap()
+}
+
+/// `primitive_root` evaluated at compile time, factoring `p - 1` by trial
+/// division. Meant for NTT-sized primes.
+const fn primitive_root_const(p: u64) -> u64 {
+    if p == 2 {
+        return 1;
+    }
+    // A u64 has at most 15 distinct prime factors.
+    let (mut factors, mut count) = ([0u64; 16], 0);
+    let (mut rest, mut q) = (p - 1, 2);
+    while q <= rest / q {
+        if rest.is_multiple_of(q) {
+            factors[count] = q;
+            count += 1;
+            while rest.is_multiple_of(q) {
+                rest /= q;
+            }
+        }
+        q += 1;
+    }
+    if rest > 1 {
+        factors[count] = rest;
+        count += 1;
+    }
+    let mut g = 2;
+    while g < p {
+        let mut i = 0;
+        while i < count && mod_pow(g, (p - 1) / factors[i], p) != 1 {
+            i += 1;
+        }
+        if i == count {
+            return g;
+        }
+        g += 1;
+    }
+    panic!("modulus must be a prime")
+}
+
+impl<const M: u64> ModInt<M> {
+    /// Smallest primitive root of `M`, computed once per modulus.
+    const ROOT: Self = Self { value: primitive_root_const(M) };
+}
+
+/// In-place number-theoretic transform modulo the prime `M`; `invert`
+/// applies the inverse transform, scaling included. The length must be a
+/// power of two dividing `M - 1` (up to 2^23 for 998244353).
+pub fn ntt<const M: u64>(a: &mut [ModInt<M>], invert: bool) {
+    let n = a.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(
+        n.is_power_of_two() && (M - 1).is_multiple_of(n as u64),
+        "length must be a power of two dividing M - 1"
+    );
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let root = ModInt::<M>::ROOT;
+    let mut len = 2;
+    while len <= n {
+        let mut w = root.pow((M - 1) / len as u64);
+        if invert {
+            w = w.inv().unwrap();
+        }
+        let half = len / 2;
+        let mut twiddles = Vec::with_capacity(half);
+        let mut cur = ModInt::new(1);
+        for _ in 0..half {
+            twiddles.push(cur);
+            cur *= w;
+        }
+        for chunk in a.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            for ((x, y), &t) in lo.iter_mut().zip(hi.iter_mut()).zip(&twiddles) {
+                let (u, v) = (*x, *y * t);
+                *x = u + v;
+                *y = u - v;
+            }
+        }
+        len <<= 1;
+    }
+    if invert {
+        let inv_n = ModInt::<M>::new(n as u64).inv().unwrap();
+        for x in a.iter_mut() {
+            *x *= inv_n;
+        }
+    }
+}
+
+/// Product of two polynomials modulo the NTT-friendly prime `M`.
+/// Example: convolution of [1, 1] and [1, 1] is [1, 2, 1].
+pub fn convolution<const M: u64>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let len = a.len() + b.len() - 1;
+    if a.len().min(b.len()) <= 32 {
+        let mut res = vec![ModInt::new(0); len];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                res[i + j] += x * y;
+            }
+        }
+        return res;
+    }
+    let size = len.next_power_of_two();
+    let mut fa = a.to_vec();
+    let mut fb = b.to_vec();
+    fa.resize(size, ModInt::new(0));
+    fb.resize(size, ModInt::new(0));
+    ntt(&mut fa, false);
+    ntt(&mut fb, false);
+    for (x, &y) in fa.iter_mut().zip(&fb) {
+        *x *= y;
+    }
+    ntt(&mut fa, true);
+    fa.truncate(len);
+    fa
+}
+
+const NTT_PRIMES: [u64; 3] = [998_244_353, 167_772_161, 469_762_049];
+
+fn convolution_residues<const M: u64>(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let a: Vec<ModInt<M>> = a.iter().map(|&x| ModInt::new(x)).collect();
+    let b: Vec<ModInt<M>> = b.iter().map(|&x| ModInt::new(x)).collect();
+    convolution(&a, &b).into_iter().map(ModInt::value).collect()
+}
+
+/// Exact convolution of non-negative sequences through three NTT primes and
+/// Garner's recombination. Exact while every output coefficient stays below
+/// their product, about 7.8 * 10^25.
+pub fn convolution_exact(a: &[u64], b: &[u64]) -> Vec<u128> {
+    let [p1, p2, p3] = NTT_PRIMES;
+    let r1 = convolution_residues::<998_244_353>(a, b);
+    let r2 = convolution_residues::<167_772_161>(a, b);
+    let r3 = convolution_residues::<469_762_049>(a, b);
+    let inv_p1 = inverse_mod_u64(p1 % p2, p2).unwrap() as u128;
+    let inv_p1p2 = inverse_mod_u64(mod_mul(p1, p2, p3), p3).unwrap() as u128;
+    let (p1, p2, p3) = (p1 as u128, p2 as u128, p3 as u128);
+    r1.iter()
+        .zip(&r2)
+        .zip(&r3)
+        .map(|((&x1, &x2), &x3)| {
+            let x1 = x1 as u128;
+            let t2 = (x2 as u128 + p2 - x1 % p2) % p2 * inv_p1 % p2;
+            let y = x1 + p1 * t2;
+            let t3 = (x3 as u128 + p3 - y % p3) % p3 * inv_p1p2 % p3;
+            y + p1 * p2 * t3
+        })
+        .collect()
+}
+
+/// Convolution modulo any `m`, through `convolution_exact`; needs
+/// `min(a.len(), b.len()) * (m - 1)^2` below about 7.8 * 10^25, which holds
+/// for `m` around 10^9 and lengths up to 10^7.
+pub fn convolution_mod(a: &[u64], b: &[u64], m: u64) -> Vec<u64> {
+    assert!(m > 0, "modulus must be above zero");
+    let bound = NTT_PRIMES.iter().map(|&p| p as u128).product::<u128>();
+    let terms = a.len().min(b.len()) as u128;
+    let max = (m as u128 - 1) * (m as u128 - 1);
+    assert!(max.saturating_mul(terms) < bound, "coefficients would exceed the exact range");
+    let a: Vec<u64> = a.iter().map(|&x| x % m).collect();
+    let b: Vec<u64> = b.iter().map(|&x| x % m).collect();
+    convolution_exact(&a, &b)
+        .into_iter()
+        .map(|x| (x % m as u128) as u64)
+        .collect()
+}
+
+/// First `n` coefficients of `1 / a` modulo the NTT-friendly prime `M`, by
+/// Newton iteration; needs `a[0] != 0`.
+pub fn poly_inv<const M: u64>(a: &[ModInt<M>], n: usize) -> Vec<ModInt<M>> {
+    assert!(!a.is_empty() && a[0].value() != 0, "constant term must be invertible");
+    let mut b = vec![a[0].inv().expect("constant term must be invertible")];
+    let mut len = 1;
+    while len < n {
+        len *= 2;
+        // b <- b * (2 - a * b) mod x^len
+        let mut t = convolution(&a[..a.len().min(len)], &b);
+        t.truncate(len);
+        for x in t.iter_mut() {
+            *x = -*x;
+        }
+        t[0] += ModInt::new(2);
+        b = convolution(&b, &t);
+        b.truncate(len);
+    }
+    b.truncate(n);
+    b
+}
+
+/// Inverses of `1..=n` modulo the prime `M > n`, with `inv[0]` unused.
+fn inverse_table<const M: u64>(n: usize) -> Vec<ModInt<M>> {
+    let mut inv = vec![ModInt::new(1); n + 1];
+    for i in 2..=n {
+        inv[i] = -ModInt::new(M / i as u64) * inv[(M % i as u64) as usize];
+    }
+    inv
+}
+
+/// First `n` coefficients of `ln(a)`; needs `a[0] == 1`.
+pub fn poly_log<const M: u64>(a: &[ModInt<M>], n: usize) -> Vec<ModInt<M>> {
+    assert!(!a.is_empty() && a[0].value() == 1, "constant term must be 1");
+    if n == 0 {
+        return Vec::new();
+    }
+    let derivative: Vec<ModInt<M>> = a
+        .iter()
+        .enumerate()
+        .skip(1)
+        .take(n)
+        .map(|(i, &x)| x * ModInt::new(i as u64))
+        .collect();
+    let mut quotient = convolution(&derivative, &poly_inv(a, n));
+    quotient.resize(n - 1, ModInt::new(0));
+    let inv = inverse_table::<M>(n);
+    let mut res = vec![ModInt::new(0); n];
+    for (i, &x) in quotient.iter().enumerate() {
+        res[i + 1] = x * inv[i + 1];
+    }
+    res
+}
+
+/// First `n` coefficients of `exp(a)`, by Newton iteration; needs
+/// `a[0] == 0`.
+pub fn poly_exp<const M: u64>(a: &[ModInt<M>], n: usize) -> Vec<ModInt<M>> {
+    assert!(a.is_empty() || a[0].value() == 0, "constant term must be 0");
+    let mut g = vec![ModInt::new(1)];
+    let mut len = 1;
+    while len < n {
+        len *= 2;
+        // g <- g * (1 - ln(g) + a) mod x^len
+        let ln = poly_log(&g, len);
+        let mut t: Vec<ModInt<M>> = (0..len)
+            .map(|i| a.get(i).copied().unwrap_or_default() - ln[i])
+            .collect();
+        t[0] += ModInt::new(1);
+        g = convolution(&g, &t);
+        g.truncate(len);
+    }
+    g.truncate(n);
+    g
+}
+
+/// Chinese remainder theorem over `(residue, modulus)` pairs; moduli need
+/// not be coprime. Returns `(x, lcm)` with `0 <= x < lcm`, or `None` when
+/// the congruences conflict or the lcm overflows `u64`.
+/// Example: crt(&[(2, 3), (3, 5), (2, 7)]) -> Some((23, 105)).
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let (mut x, mut m) = (0u128, 1u128);
+    for &(r, n) in congruences {
+        assert!(n > 0, "moduli must be above zero");
+        let r = (r % n) as u128;
+        let n = n as u128;
+        let g = gcd(m as u64, n as u64) as u128;
+        let diff = (r + n - x % n) % n;
+        if !diff.is_multiple_of(g) {
+            return None;
+        }
+        // Solve m * t = diff (mod n) for t.
+        let (m_g, n_g) = (m / g, n / g);
+        let inv = inverse_mod_u64((m_g % n_g) as u64, n_g as u64)? as u128;
+        let t = (diff / g) % n_g * inv % n_g;
+        let lcm = m * n_g;
+        if lcm > u64::MAX as u128 {
+            return None;
+        }
+        x = (x + m * t) % lcm;
+        m = lcm;
+    }
+    Some((x as u64, m as u64))
+}
+
+fn gcd_i128(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+/// Full 256-bit product of two magnitudes, as `(high, low)` halves.
+fn wide_mul(x: u128, y: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (x0, x1, y0, y1) = (x & mask, x >> 64, y & mask, y >> 64);
+    let (p00, p01, p10, p11) = (x0 * y0, x0 * y1, x1 * y0, x1 * y1);
+    let mid = (p00 >> 64) + (p01 & mask) + (p10 & mask);
+    let low = (p00 & mask) | (mid << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (high, low)
+}
+
+/// `(a * b - c * d) / e` for an exact division. The products are formed in
+/// 256 bits, so only the quotient has to fit in `i128`; `None` if it does not.
+fn cross_div(a: i128, b: i128, c: i128, d: i128, e: i128) -> Option<i128> {
+    let narrow = a.checked_mul(b).zip(c.checked_mul(d));
+    if let Some(v) = narrow.and_then(|(x, y)| x.checked_sub(y)) {
+        return Some(v / e);
+    }
+    // Signed 256-bit values as (negative, (high, low)) magnitudes.
+    let x = ((a < 0) != (b < 0), wide_mul(a.unsigned_abs(), b.unsigned_abs()));
+    let y = ((c < 0) == (d < 0), wide_mul(c.unsigned_abs(), d.unsigned_abs()));
+    let (neg, (high, low)) = if x.0 == y.0 {
+        let (low, carry) = x.1.1.overflowing_add(y.1.1);
+        (x.0, (x.1.0 + y.1.0 + carry as u128, low))
+    } else {
+        let (big, small) = if x.1 >= y.1 { (x, y) } else { (y, x) };
+        let (low, borrow) = big.1.1.overflowing_sub(small.1.1);
+        (big.0, (big.1.0 - small.1.0 - borrow as u128, low))
+    };
+    let divisor = e.unsigned_abs();
+    if high >= divisor {
+        return None;
+    }
+    // Long division, one bit at a time; the remainder stays below `divisor`.
+    let (mut rem, mut quot) = (high, 0u128);
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((low >> i) & 1);
+        quot <<= 1;
+        if carry == 1 || rem >= divisor {
+            rem = rem.wrapping_sub(divisor);
+            quot |= 1;
+        }
+    }
+    let quot = i128::try_from(quot).ok()?;
+    Some(if neg != (e < 0) { -quot } else { quot })
+}
+
+/// Exact rational `num / den`, kept in lowest terms with `den > 0`.
+/// Arithmetic panics instead of wrapping when a result leaves `i128`.
+/// Example: Fraction::new(6, -4).to_string() == "-3/2".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fraction {
+    num: i128,
+    den: i128,
+}
+
+impl Fraction {
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "denominator must be non-zero");
+        let g = gcd_i128(num, den);
+        let (num, den) = (num / g, den / g);
+        if den < 0 {
+            Self {
+                num: num.checked_neg().expect("fraction overflows i128"),
+                den: -den,
+            }
+        } else {
+            Self { num, den }
+        }
+    }
+
+    pub fn numer(self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.num == 0
+    }
+}
+
+impl From<i64> for Fraction {
+    fn from(value: i64) -> Self {
+        Self {
+            num: value as i128,
+            den: 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Fraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl std::ops::Add for Fraction {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let g = gcd_i128(self.den, rhs.den);
+        let num = self
+            .num
+            .checked_mul(rhs.den / g)
+            .zip(rhs.num.checked_mul(self.den / g))
+            .and_then(|(x, y)| x.checked_add(y));
+        let den = (self.den / g).checked_mul(rhs.den);
+        let (num, den) = num.zip(den).expect("fraction overflows i128");
+        Self::new(num, den)
+    }
+}
+
+impl std::ops::Sub for Fraction {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl std::ops::Mul for Fraction {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        // Cross-cancel first so the products stay small.
+        let g1 = gcd_i128(self.num, rhs.den).max(1);
+        let g2 = gcd_i128(rhs.num, self.den).max(1);
+        let num = (self.num / g1).checked_mul(rhs.num / g2);
+        let den = (self.den / g2).checked_mul(rhs.den / g1);
+        let (num, den) = num.zip(den).expect("fraction overflows i128");
+        Self::new(num, den)
+    }
+}
+
+impl std::ops::Div for Fraction {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        assert!(!rhs.is_zero(), "division by zero");
+        self * Self::new(rhs.den, rhs.num)
+    }
+}
+
+impl std::ops::Neg for Fraction {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            num: self.num.checked_neg().expect("fraction overflows i128"),
+            den: self.den,
+        }
+    }
+}
+
+/// Dense integer matrix. `rank`, `det` and `inverse` are exact (over the
+/// rationals); the `_mod` variants first reduce every entry into `[0, m)`.
+/// Printing gives one space-separated row per line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<Vec<i64>>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![vec![0; cols]; rows],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zeros(n, n);
+        for i in 0..n {
+            m.data[i][i] = 1;
+        }
+        m
+    }
+
+    pub fn from_rows(data: Vec<Vec<i64>>) -> Self {
+        let cols = data.first().map_or(0, |row| row.len());
+        assert!(
+            data.iter().all(|row| row.len() == cols),
+            "all rows must have the same length"
+        );
+        Self {
+            rows: data.len(),
+            cols,
+            data,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn as_rows(&self) -> &[Vec<i64>] {
+        &self.data
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<i64>> {
+        self.data
+    }
+
+    pub fn transpose(&self) -> Self {
+        let data = (0..self.cols)
+            .map(|j| self.data.iter().map(|row| row[j]).collect())
+            .collect();
+        Self {
+            rows: self.cols,
+            cols: self.rows,
+            data,
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        *self == self.transpose()
+    }
+
+    fn reduced(&self, m: u64) -> Vec<Vec<u64>> {
+        assert!(m > 0, "modulus must be above zero");
+        self.data
+            .iter()
+            .map(|row| row.iter().map(|&x| (x as i128).rem_euclid(m as i128) as u64).collect())
+            .collect()
+    }
+
+    fn from_residues(data: Vec<Vec<u64>>) -> Self {
+        Self::from_rows(
+            data.into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|x| i64::try_from(x).expect("residue exceeds i64"))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Product modulo `m`, entries in `[0, m)`.
+    pub fn mul_mod(&self, other: &Matrix, m: u64) -> Matrix {
+        assert_eq!(self.cols, other.rows, "inner dimensions must agree");
+        let (a, b) = (self.reduced(m), other.reduced(m));
+        let data = a
+            .iter()
+            .map(|row| {
+                (0..other.cols)
+                    .map(|j| {
+                        let s = row.iter().zip(&b).fold(0u128, |acc, (&x, brow)| {
+                            (acc + x as u128 * brow[j] as u128) % m as u128
+                        });
+                        s as u64
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::from_residues(data)
+    }
+
+    /// `self^e` modulo `m` by repeated squaring; `e = 0` gives the identity.
+    /// Example: [[1, 1], [1, 0]]^n holds Fibonacci numbers.
+    pub fn pow_mod(&self, mut e: u64, m: u64) -> Matrix {
+        assert!(self.is_square(), "matrix must be square");
+        let mut base = Self::from_residues(self.reduced(m));
+        let mut res = Self::from_residues(Self::identity(self.rows).reduced(m));
+        while e > 0 {
+            if e & 1 == 1 {
+                res = res.mul_mod(&base, m);
             }
-            res.push((p, e));
+            base = base.mul_mod(&base, m);
+            e >>= 1;
         }
-        if p == 2 {
-            p = 3;
-        } else {
-            p += 2;
+        res
+    }
+
+    /// Fraction-free (Bareiss) elimination. Every intermediate entry is a
+    /// minor of the matrix, so values stay within the Hadamard bound; the
+    /// cross products are formed in 256 bits before the exact division.
+    /// Returns the rank and the determinant of the leading pivot block, or
+    /// `None` once a minor no longer fits in `i128`.
+    fn bareiss(&self) -> Option<(usize, i128)> {
+        let mut a: Vec<Vec<i128>> = self
+            .data
+            .iter()
+            .map(|row| row.iter().map(|&x| x as i128).collect())
+            .collect();
+        let (mut rank, mut prev, mut sign) = (0, 1i128, 1i128);
+        for c in 0..self.cols {
+            let Some(p) = (rank..self.rows).find(|&r| a[r][c] != 0) else {
+                continue;
+            };
+            if p != rank {
+                a.swap(p, rank);
+                sign = -sign;
+            }
+            let pivot_row = a[rank].clone();
+            for row in a.iter_mut().skip(rank + 1) {
+                let factor = row[c];
+                for (x, &y) in row.iter_mut().zip(&pivot_row).skip(c) {
+                    *x = cross_div(pivot_row[c], *x, factor, y, prev)?;
+                }
+            }
+            prev = pivot_row[c];
+            rank += 1;
         }
+        Some((rank, sign * prev))
+    }
+
+    /// Exact rank, as the largest `rank_mod` over enough primes near `2^62`
+    /// that their product exceeds the Hadamard bound on every minor. Never
+    /// overflows, whatever the size of the entries.
+    pub fn rank(&self) -> usize {
+        let k = self.rows.min(self.cols);
+        // log2 of the product of the k largest row norms (at least 1 each).
+        let mut norms: Vec<f64> = self
+            .data
+            .iter()
+            .map(|row| row.iter().map(|&x| (x as f64) * (x as f64)).sum::<f64>())
+            .map(|sq| sq.max(1.0).log2() / 2.0)
+            .collect();
+        norms.sort_by(|x, y| y.total_cmp(x));
+        let bound: f64 = norms.iter().take(k).sum::<f64>() + 2.0;
+        let (mut rank, mut bits, mut p) = (0, 0.0, 1u64 << 62);
+        while rank < k && bits < bound {
+            p -= 1;
+            if is_prime_u64(p) {
+                rank = rank.max(self.rank_mod(p));
+                bits += 61.0;
+            }
+        }
+        rank
     }
-    if n > 1 {
-        res.push((n, 1));
-    }
-    res
-}
 
-/// Return the nth (0-based) permutation of the input string.
-/// Example: perm(3, "abc") -> "bac".
-pub fn perm(mut n: u64, s: &str) -> String {
-    let mut chars: Vec<char> = s.chars().collect();
-    let len = chars.len();
-    if len == 0 {
-        return String::new();
+    /// Exact determinant. Example: det([[2, 1], [7, 4]]) == 1.
+    /// Panics when a minor does not fit in `i128`, which random 6x6 matrices
+    /// with entries near 1e9 already reach; see `checked_det` and `det_mod`.
+    pub fn det(&self) -> i128 {
+        self.checked_det().expect("matrix minors exceed i128, use det_mod")
     }
 
-    let mut fact = vec![1u64; len + 1];
-    for i in 1..=len {
-        fact[i] = fact[i - 1].saturating_mul(i as u64);
+    /// Exact determinant, or `None` when a minor does not fit in `i128`.
+    pub fn checked_det(&self) -> Option<i128> {
+        assert!(self.is_square(), "matrix must be square");
+        match self.bareiss()? {
+            (rank, det) if rank == self.rows => Some(det),
+            _ => Some(0),
+        }
     }
-    if fact[len] > 0 {
-        n %= fact[len];
+
+    /// Exact inverse, or `None` when singular or when a minor does not fit
+    /// in `i128` (use `inverse_mod` for such entries). Fraction-free
+    /// Gauss-Jordan turns `[A | I]` into `[d*I | adj]` with `d = ±det(A)`,
+    /// so every intermediate is a minor; each entry is then reduced over `d`.
+    pub fn inverse(&self) -> Option<Vec<Vec<Fraction>>> {
+        assert!(self.is_square(), "matrix must be square");
+        let n = self.rows;
+        let mut a: Vec<Vec<i128>> = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let unit = (0..n).map(|j| (i == j) as i128);
+                row.iter().map(|&x| x as i128).chain(unit).collect()
+            })
+            .collect();
+        let mut prev = 1i128;
+        for c in 0..n {
+            let p = (c..n).find(|&r| a[r][c] != 0)?;
+            a.swap(p, c);
+            let pivot_row = a[c].clone();
+            for (r, row) in a.iter_mut().enumerate() {
+                if r == c {
+                    continue;
+                }
+                let factor = row[c];
+                for (x, &y) in row.iter_mut().zip(&pivot_row) {
+                    *x = cross_div(pivot_row[c], *x, factor, y, prev)?;
+                }
+            }
+            prev = pivot_row[c];
+        }
+        let inv = a
+            .into_iter()
+            .map(|row| row[n..].iter().map(|&x| Fraction::new(x, prev)).collect())
+            .collect();
+        Some(inv)
+    }
+
+    /// Gauss-Jordan modulo the prime `p` on the first `pivot_cols` columns.
+    /// Returns the rank and the determinant of the eliminated block.
+    fn eliminate_mod(a: &mut [Vec<u64>], pivot_cols: usize, p: u64) -> (usize, u64) {
+        let (mut rank, mut det) = (0, 1u64);
+        for c in 0..pivot_cols {
+            let Some(r) = (rank..a.len()).find(|&r| a[r][c] != 0) else {
+                det = 0;
+                continue;
+            };
+            if r != rank {
+                a.swap(r, rank);
+                det = (p - det) % p;
+            }
+            det = mod_mul(det, a[rank][c], p);
+            let inv = inverse_mod_u64(a[rank][c], p).expect("modulus must be prime");
+            for x in a[rank].iter_mut() {
+                *x = mod_mul(*x, inv, p);
+            }
+            let pivot_row = a[rank].clone();
+            for (i, row) in a.iter_mut().enumerate() {
+                let factor = row[c];
+                if i == rank || factor == 0 {
+                    continue;
+                }
+                for (x, &y) in row.iter_mut().zip(&pivot_row) {
+                    *x = (*x + p - mod_mul(factor, y, p)) % p;
+                }
+            }
+            rank += 1;
+        }
+        (rank, det)
     }
 
-    let mut res = String::with_capacity(len);
-    for i in (1..=len).rev() {
-        let f = fact[i - 1];
-        let idx = (n / f) as usize;
-        n %= f;
-        let ch = chars.remove(idx);
-        res.push(ch);
+    /// Rank over the field of integers modulo the prime `p`.
+    pub fn rank_mod(&self, p: u64) -> usize {
+        let mut a = self.reduced(p);
+        Self::eliminate_mod(&mut a, self.cols, p).0
     }
-    res
-}
 
-/// Compute the binomial coefficient C(n, k).
-/// Example: binomial(5, 2) -> 10.
-pub fn binomial(n: u64, k: u64) -> u128 {
-    if k > n {
-        return 0;
+    /// Determinant modulo the prime `p`.
+    pub fn det_mod(&self, p: u64) -> u64 {
+        assert!(self.is_square(), "matrix must be square");
+        let mut a = self.reduced(p);
+        Self::eliminate_mod(&mut a, self.cols, p).1
     }
-    let k = k.min(n - k);
-    let mut res: u128 = 1;
-    for i in 0..k {
-        res = res * (n - i) as u128 / (i + 1) as u128;
+
+    /// Inverse modulo the prime `p`, or `None` when singular there.
+    pub fn inverse_mod(&self, p: u64) -> Option<Matrix> {
+        assert!(self.is_square(), "matrix must be square");
+        let n = self.rows;
+        let mut a = self.reduced(p);
+        for (i, row) in a.iter_mut().enumerate() {
+            row.extend((0..n).map(|j| (i == j) as u64));
+        }
+        let (rank, _) = Self::eliminate_mod(&mut a, n, p);
+        let inv = a.into_iter().map(|row| row[n..].to_vec()).collect();
+        (rank == n).then(|| Self::from_residues(inv))
+    }
+
+    /// Random `rows x cols` matrix with entries in `[lo, hi]`.
+    pub fn random(rng: &mut impl Rng, rows: usize, cols: usize, lo: i64, hi: i64) -> Matrix {
+        assert!(lo <= hi, "upper-bound should be larger than lower-bound");
+        let data = (0..rows)
+            .map(|_| (0..cols).map(|_| rng.random_range(lo..=hi)).collect())
+            .collect();
+        Self { rows, cols, data }
+    }
+
+    /// Random matrix of exactly the given rank: `L * U` where `L` is
+    /// `rows x rank`, `U` is `rank x cols`, both with entries in `[lo, hi]`
+    /// and each holding a unit-triangular block on random rows / columns.
+    /// Entries of the result can therefore reach `rank * max(|lo|, |hi|)^2`.
+    /// A square full-rank result has determinant `±1`.
+    pub fn random_with_rank(
+        rng: &mut impl Rng,
+        rows: usize,
+        cols: usize,
+        rank: usize,
+        lo: i64,
+        hi: i64,
+    ) -> Matrix {
+        use rand::seq::index::sample;
+        assert!(rank <= rows.min(cols), "rank cannot exceed the dimensions");
+        // Sampled in random order, so the pivots pair up arbitrarily.
+        let pivot_rows = sample(rng, rows, rank).into_vec();
+        let pivot_cols = sample(rng, cols, rank).into_vec();
+        let mut l = Self::random(rng, rows, rank, lo, hi);
+        let mut u = Self::random(rng, rank, cols, lo, hi);
+        for k in 0..rank {
+            for j in k..rank {
+                l.data[pivot_rows[k]][j] = (j == k) as i64;
+                u.data[j][pivot_cols[k]] = (j == k) as i64;
+            }
+        }
+        &l * &u
+    }
+
+    /// Random invertible `n x n` matrix with entries in `[lo, hi]`, by
+    /// rejection: a matrix that is non-singular modulo a large prime is
+    /// non-singular over the rationals too.
+    pub fn random_invertible(rng: &mut impl Rng, n: usize, lo: i64, hi: i64) -> Matrix {
+        assert!(n <= 1 || lo < hi, "range needs two values to be invertible");
+        assert!(lo != 0 || hi != 0, "range must contain a non-zero value");
+        loop {
+            let m = Self::random(rng, n, n, lo, hi);
+            if m.rank_mod(998_244_353) == n {
+                return m;
+            }
+        }
     }
-    res
-}
 
-/// Compute the nth Catalan number.
-/// Example: catalan_number(4) -> 14.
-pub fn catalan_number(n: u64) -> u128 {
-    let mut num: u128 = 1;
-    let mut den: u128 = 1;
-    for k in 2..=n {
-        num *= (n + k) as u128;
-        den *= k as u128;
+    /// Random `n x n` matrix invertible modulo the prime `p`, entries in `[0, p)`.
+    pub fn random_invertible_mod(rng: &mut impl Rng, n: usize, p: u64) -> Matrix {
+        assert!(p <= i64::MAX as u64, "modulus must fit in i64");
+        loop {
+            let m = Self::random(rng, n, n, 0, p as i64 - 1);
+            if m.rank_mod(p) == n {
+                return m;
+            }
+        }
     }
-    num / den
-}
 
-/// Generate primes in [2, n) with a simple sieve.
-/// Example: prime_sieve(10) -> [2, 3, 5, 7].
-pub fn prime_sieve(n: u64) -> Vec<u64> {
-    if n <= 2 {
-        return Vec::new();
+    /// Random singular `n x n` matrix of rank exactly `n - 1`, the hardest
+    /// case for solvers that only test for a zero pivot.
+    pub fn random_singular(rng: &mut impl Rng, n: usize, lo: i64, hi: i64) -> Matrix {
+        assert!(n > 0, "n must be above zero");
+        Self::random_with_rank(rng, n, n, n - 1, lo, hi)
     }
 
-    let size = (n / 2) as usize;
-    let mut sieve = vec![true; size];
-    let mut i = 3u64;
-    while i * i < n {
-        if sieve[(i / 2) as usize] {
-            let mut j = i * i;
-            let step = i * 2;
-            while j < n {
-                sieve[(j / 2) as usize] = false;
-                j += step;
+    /// Random symmetric `n x n` matrix with entries in `[lo, hi]`.
+    pub fn random_symmetric(rng: &mut impl Rng, n: usize, lo: i64, hi: i64) -> Matrix {
+        let mut m = Self::random(rng, n, n, lo, hi);
+        for i in 0..n {
+            for j in 0..i {
+                m.data[i][j] = m.data[j][i];
             }
         }
-        i += 2;
+        m
     }
 
-    let mut res = Vec::new();
-    res.push(2);
-    let mut i = 3u64;
-    while i < n {
-        if sieve[(i / 2) as usize] {
-            res.push(i);
+    /// Random `n x n` permutation matrix: row `i` has its 1 in column `p[i]`.
+    pub fn random_permutation(rng: &mut impl Rng, n: usize) -> Matrix {
+        let mut p: Vec<usize> = (0..n).collect();
+        p.shuffle(rng);
+        let mut m = Self::zeros(n, n);
+        for (i, &j) in p.iter().enumerate() {
+            m.data[i][j] = 1;
         }
-        i += 2;
+        m
+    }
+
+    /// Random diagonally dominant `n x n` matrix: off-diagonal entries in
+    /// `[lo, hi]` and `|a[i][i]| >= sum of |a[i][j]|` over `j != i`, with
+    /// strict inequality when `strict` (which makes the matrix invertible).
+    /// Diagonal signs are random.
+    pub fn random_diagonally_dominant(
+        rng: &mut impl Rng,
+        n: usize,
+        lo: i64,
+        hi: i64,
+        strict: bool,
+    ) -> Matrix {
+        let mut m = Self::random(rng, n, n, lo, hi);
+        let spread = lo.unsigned_abs().max(hi.unsigned_abs()).max(1) as i64;
+        for (i, row) in m.data.iter_mut().enumerate() {
+            let off: i64 = row
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, x)| x.abs())
+                .sum();
+            let diag = off + rng.random_range(strict as i64..=spread);
+            row[i] = if rng.random_bool(0.5) { diag } else { -diag };
+        }
+        m
     }
-    res
 }
 
-/// Extended Euclidean algorithm.
-/// Returns (x, y, gcd) such that a*x + b*y = gcd.
-pub fn exgcd(mut a: i64, mut b: i64) -> (i64, i64, i64) {
-    let (mut u, mut v, mut s, mut t) = (1i64, 0i64, 0i64, 1i64);
-    while b != 0 {
-        let q = a / b;
-        let r = a % b;
-        a = b;
-        b = r;
-        let next_u = u - q * s;
-        let next_v = v - q * t;
-        u = s;
-        v = t;
-        s = next_u;
-        t = next_v;
+impl std::ops::Mul for &Matrix {
+    type Output = Matrix;
+
+    /// Exact product; panics if an entry overflows `i64`.
+    fn mul(self, rhs: &Matrix) -> Matrix {
+        assert_eq!(self.cols, rhs.rows, "inner dimensions must agree");
+        let data = self
+            .data
+            .iter()
+            .map(|row| {
+                (0..rhs.cols)
+                    .map(|j| {
+                        row.iter()
+                            .zip(&rhs.data)
+                            .try_fold(0i64, |acc, (&x, brow)| {
+                                acc.checked_add(x.checked_mul(brow[j])?)
+                            })
+                            .expect("matrix product overflows i64")
+                    })
+                    .collect()
+            })
+            .collect();
+        Matrix {
+            rows: self.rows,
+            cols: rhs.cols,
+            data,
+        }
     }
-    (u, v, a)
 }
 
-/// Modular inverse of a modulo m, if it exists.
-pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
-    if m == 0 {
-        return None;
-    }
-    let (x, _, g) = exgcd(a, m);
-    if g != 1 && g != -1 {
-        return None;
-    }
-    let mut res = x % m;
-    if res < 0 {
-        res += m.abs();
+impl std::fmt::Display for Matrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.data.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let line: Vec<String> = row.iter().map(|x| x.to_string()).collect();
+            write!(f, "{}", line.join(" "))?;
+        }
+        Ok(())
     }
-    Some(res)
 }
 
 /// Euler's totient function.
@@ -527,15 +2856,15 @@ pub fn miu(x: u64) -> i32 {
     }
 }
 
-/// Convert a decimal number to the given base (2..=16).
+/// Convert a decimal number to the given base (2..=62), digits from `DIGITS_62`.
 pub fn dec2base(mut n: u64, base: u32) -> String {
-    if base < 2 || base > 16 {
+    if !(2..=62).contains(&base) {
         return String::new();
     }
     if n == 0 {
         return "0".to_string();
     }
-    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    const DIGITS: &[u8] = DIGITS_62.as_bytes();
     let mut buf = Vec::new();
     let b = base as u64;
     while n > 0 {
@@ -547,8 +2876,93 @@ pub fn dec2base(mut n: u64, base: u32) -> String {
     String::from_utf8(buf).unwrap_or_default()
 }
 
+/// Digit alphabet shared by the base conversions: `0-9`, `A-Z`, then `a-z`.
+pub const DIGITS_62: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// `dec2base` for signed numbers: negatives get a leading `-`.
+/// Example: dec2base_signed(-255, 16) -> "-FF".
+pub fn dec2base_signed(n: i64, base: u32) -> String {
+    let digits = dec2base(n.unsigned_abs(), base);
+    if n < 0 && !digits.is_empty() {
+        format!("-{digits}")
+    } else {
+        digits
+    }
+}
+
+/// Write `n` with a custom digit alphabet; its length is the base.
+/// Example: dec2base_alphabet(-5, "ab") -> "-bab".
+pub fn dec2base_alphabet(n: i128, alphabet: &str) -> String {
+    let digits: Vec<char> = alphabet.chars().collect();
+    check_alphabet(&digits);
+    let base = digits.len() as u128;
+    let mut m = n.unsigned_abs();
+    let mut buf = Vec::new();
+    loop {
+        buf.push(digits[(m % base) as usize]);
+        m /= base;
+        if m == 0 {
+            break;
+        }
+    }
+    if n < 0 {
+        buf.push('-');
+    }
+    buf.iter().rev().collect()
+}
+
+fn check_alphabet(digits: &[char]) {
+    assert!(digits.len() >= 2, "alphabet needs at least two digits");
+    assert!(!digits.contains(&'-'), "alphabet cannot contain '-'");
+    let mut sorted = digits.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(sorted.len(), digits.len(), "alphabet digits must be distinct");
+}
+
+/// Parse a number written by `dec2base` / `dec2base_signed`. Bases up to 36
+/// accept either letter case. `None` on bad digits, an out-of-range base or
+/// overflow. Example: base2dec("-ff", 16) -> Some(-255).
+pub fn base2dec(s: &str, base: u32) -> Option<i128> {
+    if !(2..=62).contains(&base) {
+        return None;
+    }
+    let alphabet = &DIGITS_62[..base as usize];
+    if base <= 36 {
+        base2dec_alphabet(&s.to_ascii_uppercase(), alphabet)
+    } else {
+        base2dec_alphabet(s, alphabet)
+    }
+}
+
+/// Inverse of `dec2base_alphabet`; `None` on unknown digits or overflow.
+pub fn base2dec_alphabet(s: &str, alphabet: &str) -> Option<i128> {
+    let digits: Vec<char> = alphabet.chars().collect();
+    check_alphabet(&digits);
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if body.is_empty() {
+        return None;
+    }
+    // Accumulate negatively so that i128::MIN parses too.
+    let mut acc = 0i128;
+    for c in body.chars() {
+        let d = digits.iter().position(|&x| x == c)? as i128;
+        acc = acc.checked_mul(digits.len() as i128)?.checked_sub(d)?;
+    }
+    if negative { Some(acc) } else { acc.checked_neg() }
+}
+
 /// Convert a number into English words (list form).
 pub fn n2words_list(num: u64) -> Vec<String> {
+    english_words(num, false)
+}
+
+/// Word list behind `n2words_list`; British style adds "and" after a
+/// hundred and before a final group below a hundred ("One Thousand and Five").
+fn english_words(num: u64, british: bool) -> Vec<String> {
     let units = [
         "", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine",
     ];
@@ -588,6 +3002,9 @@ pub fn n2words_list(num: u64) -> Vec<String> {
             words.push(units[h].to_string());
             words.push("Hundred".to_string());
         }
+        if british && t + u > 0 && (h >= 1 || (g == 0 && groups > 1)) {
+            words.push("and".to_string());
+        }
         if t > 1 {
             words.push(tens[t].to_string());
             if u >= 1 {
@@ -617,6 +3034,167 @@ pub fn n2words(num: u64) -> String {
     n2words_list(num).join(" ")
 }
 
+/// British English words: `n2words` with "and" where British usage puts it.
+/// Example: n2words_british(1105) -> "One Thousand One Hundred and Five".
+pub fn n2words_british(num: u64) -> String {
+    english_words(num, true).join(" ")
+}
+
+/// Words for a signed number, prefixed by "Minus" when negative.
+pub fn n2words_signed(num: i64, british: bool) -> String {
+    let words = english_words(num.unsigned_abs(), british).join(" ");
+    if num < 0 { format!("Minus {words}") } else { words }
+}
+
+/// Ordinal words. Example: n2words_ordinal(42, false) -> "Forty Second".
+pub fn n2words_ordinal(num: u64, british: bool) -> String {
+    let mut words = english_words(num, british);
+    let last = words.pop().unwrap_or_default();
+    let ordinal = match last.as_str() {
+        "One" => "First".to_string(),
+        "Two" => "Second".to_string(),
+        "Three" => "Third".to_string(),
+        "Five" => "Fifth".to_string(),
+        "Eight" => "Eighth".to_string(),
+        "Nine" => "Ninth".to_string(),
+        "Twelve" => "Twelfth".to_string(),
+        w if w.ends_with('y') => format!("{}ieth", &w[..w.len() - 1]),
+        w => format!("{w}th"),
+    };
+    words.push(ordinal);
+    words.join(" ")
+}
+
+/// Number with its English ordinal suffix. Example: ordinal(112) -> "112th".
+pub fn ordinal(num: u64) -> String {
+    let suffix = match (num % 10, num % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{num}{suffix}")
+}
+
+/// Roman numeral for `1..=3999`, `None` outside that range.
+/// Example: to_roman(1994) -> Some("MCMXCIV").
+pub fn to_roman(mut num: u32) -> Option<String> {
+    const SYMBOLS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    if !(1..=3999).contains(&num) {
+        return None;
+    }
+    let mut res = String::new();
+    for (value, symbol) in SYMBOLS {
+        while num >= value {
+            res.push_str(symbol);
+            num -= value;
+        }
+    }
+    Some(res)
+}
+
+/// Parse a Roman numeral in canonical form (as `to_roman` writes it), so
+/// "IIII" or "IC" are rejected.
+pub fn from_roman(s: &str) -> Option<u32> {
+    let value = |c| match c {
+        'I' => Some(1),
+        'V' => Some(5),
+        'X' => Some(10),
+        'L' => Some(50),
+        'C' => Some(100),
+        'D' => Some(500),
+        'M' => Some(1000),
+        _ => None,
+    };
+    let values = s.chars().map(value).collect::<Option<Vec<i64>>>()?;
+    let mut total = 0i64;
+    for (i, &v) in values.iter().enumerate() {
+        match values.get(i + 1) {
+            Some(&next) if next > v => total -= v,
+            _ => total += v,
+        }
+    }
+    let total = u32::try_from(total).ok()?;
+    (to_roman(total)? == s).then_some(total)
+}
+
+/// Chinese numeral, in everyday digits (一二三) or the financial forms
+/// (壹贰叁) used on cheques. Four-digit sections are grouped by 万, 亿,
+/// 万亿 and 亿亿, with 零 marking skipped positions; a leading 一十 is
+/// shortened to 十 in everyday form.
+/// Example: n2chinese(100_500, false) -> "十万零五百".
+pub fn n2chinese(num: u64, financial: bool) -> String {
+    let (digits, positions) = if financial {
+        (
+            ["零", "壹", "贰", "叁", "肆", "伍", "陆", "柒", "捌", "玖"],
+            ["", "拾", "佰", "仟"],
+        )
+    } else {
+        (
+            ["零", "一", "二", "三", "四", "五", "六", "七", "八", "九"],
+            ["", "十", "百", "千"],
+        )
+    };
+    const SECTIONS: [&str; 5] = ["", "万", "亿", "万亿", "亿亿"];
+    if num == 0 {
+        return digits[0].to_string();
+    }
+    let mut sections = Vec::new();
+    let mut n = num;
+    while n > 0 {
+        sections.push((n % 10_000) as usize);
+        n /= 10_000;
+    }
+    let mut res = String::new();
+    let mut need_zero = false;
+    for (idx, &section) in sections.iter().enumerate().rev() {
+        if section == 0 {
+            need_zero = !res.is_empty();
+            continue;
+        }
+        if !res.is_empty() && (need_zero || section < 1000) {
+            res.push_str(digits[0]);
+        }
+        need_zero = false;
+        let mut started = false;
+        let mut zero = false;
+        for pos in (0..4).rev() {
+            let d = section / 10usize.pow(pos as u32) % 10;
+            if d == 0 {
+                zero = started;
+                continue;
+            }
+            if zero {
+                res.push_str(digits[0]);
+                zero = false;
+            }
+            res.push_str(digits[d]);
+            res.push_str(positions[pos]);
+            started = true;
+        }
+        res.push_str(SECTIONS[idx]);
+    }
+    if !financial && res.starts_with("一十") {
+        res.drain(.."一".len());
+    }
+    res
+}
+
 pub fn is_pandigital(n: &str, s: usize) -> bool {
     if s == 0 {
         return n.is_empty();
@@ -881,6 +3459,41 @@ mod tests {
         assert_eq!(n2words_list(1000), vec!["One", "Thousand"]);
     }
 
+    #[test]
+    fn test_localized_number_formats() {
+        assert_eq!(dec2base(61, 62), "z");
+        assert_eq!(dec2base_signed(-255, 16), "-FF");
+        assert_eq!(base2dec("-ff", 16), Some(-255));
+        assert_eq!(base2dec("zz", 62), Some(62 * 62 - 1));
+        assert_eq!(base2dec("12", 2), None);
+        assert_eq!(dec2base_alphabet(-5, "ab"), "-bab");
+        assert_eq!(base2dec_alphabet("-bab", "ab"), Some(-5));
+        assert_eq!(base2dec_alphabet(&dec2base_alphabet(i128::MIN, "01"), "01"), Some(i128::MIN));
+
+        assert_eq!(to_roman(1994).as_deref(), Some("MCMXCIV"));
+        assert_eq!(from_roman("MCMXCIV"), Some(1994));
+        assert_eq!(from_roman("IIII"), None);
+        assert_eq!(to_roman(4000), None);
+
+        assert_eq!(n2words_british(1105), "One Thousand One Hundred and Five");
+        assert_eq!(n2words_british(2_000_030), "Two Million and Thirty");
+        assert_eq!(n2words_signed(-115, true), "Minus One Hundred and Fifteen");
+        assert_eq!(n2words_ordinal(42, false), "Forty Second");
+        assert_eq!(n2words_ordinal(90, false), "Ninetieth");
+        assert_eq!(n2words_ordinal(1_000_000, false), "One Millionth");
+        assert_eq!(ordinal(1), "1st");
+        assert_eq!(ordinal(112), "112th");
+        assert_eq!(ordinal(23), "23rd");
+
+        assert_eq!(n2chinese(0, false), "零");
+        assert_eq!(n2chinese(15, false), "十五");
+        assert_eq!(n2chinese(1015, false), "一千零一十五");
+        assert_eq!(n2chinese(100_500, false), "十万零五百");
+        assert_eq!(n2chinese(100_000_001, false), "一亿零一");
+        assert_eq!(n2chinese(10_001_000, false), "一千万一千");
+        assert_eq!(n2chinese(15, true), "壹拾伍");
+    }
+
     #[test]
     fn test_math_edge_cases() {
         assert!(is_perm(0, 0));
@@ -997,6 +3610,313 @@ mod tests {
     fn test_divisor_sum_large_number() {
         assert_eq!(divisor_sum(100), 217);
     }
+
+    #[test]
+    fn test_special_number_generators() {
+        let mut rng = rand::rng();
+        let p = random_prime(&mut rng, 100, 200).unwrap();
+        assert!((100..=200).contains(&p) && is_prime(p));
+        assert_eq!(random_prime(&mut rng, 24, 28), None);
+        assert!([11, 13].contains(&random_prime_bits(&mut rng, 4)));
+
+        let p = random_safe_prime(&mut rng, 1000, 5000).unwrap();
+        assert!(is_prime(p) && is_prime((p - 1) / 2));
+
+        let (n, p, q) = random_semiprime(&mut rng, 1_000_000, Some(50));
+        assert_eq!(n, p * q);
+        assert!(p <= q && q - p <= 50 && is_prime(p) && is_prime(q));
+
+        assert_eq!(
+            highly_composite(60).iter().map(|&(n, _)| n).collect::<Vec<_>>(),
+            vec![1, 2, 4, 6, 12, 24, 36, 48, 60]
+        );
+        assert_eq!(random_many_divisors(&mut rng, 1000, 1), (840, 32));
+
+        assert!(is_carmichael(561));
+        assert!(!is_carmichael(563));
+        let n = random_carmichael(&mut rng, 10_000_000).unwrap();
+        assert!(is_carmichael(n));
+
+        let n = random_smooth(&mut rng, 1_000_000, 7);
+        assert!(n <= 1_000_000 && factor(n).iter().all(|&(p, _)| p <= 7));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_big_number_functions() {
+        assert_eq!(binomial_big(100, 50).to_string(), "100891344545564193334812497256");
+        assert_eq!(binomial_big(60, 30).to_u128(), Some(binomial(60, 30)));
+        assert_eq!(binomial_big(3, 5), BigUint::zero());
+        assert_eq!(catalan_number_big(15).to_u128(), Some(catalan_number(15)));
+        assert_eq!(catalan_number_big(100).digit_count(), 57);
+        assert_eq!(fibonacci_big(90).to_u64(), Some(fibonacci(90)));
+        assert_eq!(fibonacci_big(100).to_string(), "354224848179261915075");
+        assert_eq!(fibonacci_big(10_000).digit_count(), 2090);
+        assert_eq!(dec2base_big(&BigUint::from(255u64), 16), dec2base(255, 16));
+        for base in 2..=62 {
+            for n in [0, 61, 255, 1 << 40, u64::MAX] {
+                assert_eq!(dec2base_big(&BigUint::from(n), base), dec2base(n, base));
+            }
+        }
+        assert_eq!(dec2base_big(&BigUint::from(255u64), 63), "");
+    }
+
+    #[test]
+    fn test_mod_int_and_combinatorics() {
+        type Mint = ModInt1000000007;
+        let a = Mint::new(1_000_000_006);
+        assert_eq!((a + Mint::new(5)).value(), 4);
+        assert_eq!((Mint::new(3) - Mint::new(5)).value(), 1_000_000_005);
+        assert_eq!(Mint::from(-1i64), a);
+        assert_eq!(Mint::from(-1), a);
+        assert_eq!(ModInt998244353::from(5).value(), 5);
+        assert_eq!(-Mint::new(1), a);
+        assert_eq!((Mint::new(2).pow(30)).value(), 73_741_817);
+        assert_eq!(Mint::new(7) / Mint::new(7), Mint::new(1));
+        assert_eq!(ModInt::<6>::new(4).inv(), None);
+        assert_eq!(ModInt::<{ u64::MAX }>::new(u64::MAX - 1) + ModInt::new(3), ModInt::new(2));
+
+        let table = FactorialTable::<998_244_353>::new(1000);
+        assert_eq!(table.binomial(10, 3).value(), 120);
+        assert_eq!(table.binomial(3, 10).value(), 0);
+        assert_eq!(table.permutation(5, 2).value(), 20);
+        assert_eq!(table.catalan(10).value(), 16796);
+        assert_eq!(table.binomial(1000, 500).value(), binomial_mod(1000, 500, 998_244_353));
+
+        assert_eq!(binomial_mod(10, 3, 7), 1);
+        let lucas = Lucas::new(13);
+        assert_eq!(binomial_mod(1_000_000_000_000, 123_456, 13), lucas.binomial(1_000_000_000_000, 123_456));
+        assert_eq!(Lucas::new(5).binomial(20, 5), (binomial(20, 5) % 5) as u64);
+
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_linear_and_segmented_sieve() {
+        let sieve = linear_sieve(100);
+        assert_eq!(sieve.primes, prime_sieve(101));
+        for x in 1..=100u64 {
+            let i = x as usize;
+            assert_eq!(sieve.phi[i] as u64, phi(x));
+            assert_eq!(sieve.mu[i] as i32, miu(x));
+            assert_eq!(sieve.divisor_sum[i], divisor_sum(x));
+            assert_eq!(sieve.divisor_count[i] as usize, (1..=x).filter(|d| x % d == 0).count());
+            assert_eq!(sieve.factor(i), factor(x));
+        }
+        assert_eq!(sieve.spf[91], 7);
+
+        let lo = 1_000_000_000_000;
+        let primes = segmented_sieve(lo, lo + 1000);
+        assert!(primes.iter().all(|&p| is_prime(p)));
+        assert_eq!(primes.len(), (lo..=lo + 1000).filter(|&x| is_prime(x)).count());
+        assert_eq!(segmented_sieve(0, 20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert_eq!(segmented_sieve(20, 10), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_ntt_convolution_and_polynomials() {
+        type Mint = ModInt998244353;
+        let to_mint = |v: &[u64]| v.iter().map(|&x| Mint::new(x)).collect::<Vec<_>>();
+        assert_eq!(primitive_root(998_244_353), 3);
+        for p in [2, 7, 167_772_161, 469_762_049, 1_000_000_007] {
+            assert_eq!(primitive_root_const(p), primitive_root(p));
+        }
+        assert_eq!(Mint::ROOT, Mint::new(3));
+
+        let a: Vec<u64> = (1..=100).collect();
+        let b: Vec<u64> = (1..=80).rev().collect();
+        let mut naive = vec![0u64; 179];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                naive[i + j] += x * y;
+            }
+        }
+        assert_eq!(convolution(&to_mint(&a), &to_mint(&b)), to_mint(&naive));
+        assert_eq!(
+            convolution_exact(&[u32::MAX as u64; 40], &[u32::MAX as u64; 40])[39],
+            40 * (u32::MAX as u128).pow(2)
+        );
+        assert_eq!(convolution_mod(&[3, 4], &[5, 6], 7), vec![1, 3, 3]);
+
+        let f = to_mint(&[1, 2, 3, 4, 5]);
+        let inv = poly_inv(&f, 64);
+        let product = convolution(&f, &inv);
+        assert!(product[..64].iter().enumerate().all(|(i, x)| x.value() == (i == 0) as u64));
+        assert_eq!(poly_exp(&poly_log(&f, 64), 64)[..5], f[..]);
+        // exp(x) = sum x^i / i!
+        let e = poly_exp(&to_mint(&[0, 1]), 8);
+        assert_eq!(e[5] * Mint::new(120), Mint::new(1));
+    }
+
+    #[test]
+    fn test_matrix_algebra_and_generators() {
+        let fib = Matrix::from_rows(vec![vec![1, 1], vec![1, 0]]);
+        let f90 = fib.pow_mod(90, 1_000_000_007).as_rows()[0][1] as u64;
+        assert_eq!(f90, fibonacci(90) % 1_000_000_007);
+        let a = Matrix::from_rows(vec![vec![2, 1], vec![7, 4]]);
+        assert_eq!(a.det(), 1);
+        let inv = a.inverse().unwrap();
+        assert_eq!(inv[0][0], Fraction::from(4));
+        assert_eq!(inv[1][0], Fraction::from(-7));
+        let b = Matrix::from_rows(vec![vec![2, 0], vec![0, 4]]);
+        assert_eq!(b.inverse().unwrap()[1][1].to_string(), "1/4");
+        assert_eq!(b.det_mod(7), 1);
+        let b_inv = b.inverse_mod(7).unwrap();
+        assert_eq!(b.mul_mod(&b_inv, 7), Matrix::identity(2));
+
+        let mut rng = rand::rng();
+        for rank in 0..=4 {
+            let m = Matrix::random_with_rank(&mut rng, 4, 6, rank, -5, 5);
+            assert_eq!(m.rank(), rank);
+        }
+        let s = Matrix::random_singular(&mut rng, 5, -9, 9);
+        assert_eq!((s.rank(), s.det()), (4, 0));
+        let i = Matrix::random_invertible(&mut rng, 5, -9, 9);
+        assert_ne!(i.det(), 0);
+        assert!(Matrix::random_symmetric(&mut rng, 5, -9, 9).is_symmetric());
+        let p = Matrix::random_permutation(&mut rng, 5);
+        assert_eq!(&p * &p.transpose(), Matrix::identity(5));
+        let d = Matrix::random_diagonally_dominant(&mut rng, 5, -9, 9, true);
+        assert_eq!(d.rank(), 5);
+
+        // 1e9 times a 4x4 Hadamard matrix: the determinant 16e36 still fits.
+        let g = 1_000_000_000;
+        let h = Matrix::from_rows(vec![
+            vec![g, g, g, g],
+            vec![g, -g, g, -g],
+            vec![g, g, -g, -g],
+            vec![g, -g, -g, g],
+        ]);
+        assert_eq!(h.det(), 16 * 10i128.pow(36));
+        assert_eq!(h.rank(), 4);
+        let w = Matrix::random(&mut rng, 4, 4, -g, g);
+        assert_eq!(w.det().rem_euclid(998_244_353) as u64, w.det_mod(998_244_353));
+        assert_eq!(w.rank(), w.rank_mod(998_244_353));
+        // Minors of 1e9-sized 8x8 matrices reach 1e84; nothing panics.
+        let big = Matrix::random_invertible(&mut rng, 8, -g, g);
+        assert_eq!(big.checked_det(), None);
+        assert_eq!(big.inverse(), None);
+        assert_eq!(big.rank(), 8);
+    }
+
+    #[test]
+    fn test_matrix_inverse_with_large_entries() {
+        let mut rng = rand::rng();
+        for n in [4, 5, 4, 5] {
+            let a = Matrix::random_invertible(&mut rng, n, -1_000_000, 1_000_000);
+            let inv = a.inverse().unwrap();
+            // Scale by the common denominator so A * inv(A) == I stays in i128.
+            let lcm = inv
+                .iter()
+                .flatten()
+                .fold(1, |l, x| l / gcd_i128(l, x.denom()) * x.denom());
+            for (i, row) in a.as_rows().iter().enumerate() {
+                for j in 0..n {
+                    let sum: i128 = row
+                        .iter()
+                        .zip(&inv)
+                        .map(|(&x, r)| x as i128 * r[j].numer() * (lcm / r[j].denom()))
+                        .sum();
+                    assert_eq!(sum, (i == j) as i128 * lcm);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_modular_fibonacci_and_recurrences() {
+        const P: u64 = 998_244_353;
+        for n in 0..=90 {
+            assert_eq!(fibonacci_mod(n, 1_000_000_007), fibonacci(n) % 1_000_000_007);
+            assert_eq!(linear_recurrence(&[1, 1], &[0, 1], n, P), fibonacci(n) % P);
+        }
+        assert_eq!(pisano_period(10), 60);
+        assert_eq!(pisano_period(1000), 1500);
+        let n = 1_000_000_000_000_000_000;
+        assert_eq!(fibonacci_mod(n, 1000), fibonacci_mod(n % 1500, 1000));
+        let m = u64::MAX - 58;
+        let (mut a, mut b) = (0u128, 1u128);
+        for _ in 0..1000 {
+            (a, b) = (b, (a + b) % m as u128);
+        }
+        assert_eq!(fibonacci_mod(1000, m), a as u64);
+
+        // a[i] = 2 a[i-1] - a[i-2] + 3 a[i-3], recovered from its first terms.
+        let mut seq = vec![1u64, 4, 9];
+        for i in 3..12 {
+            seq.push((2 * seq[i - 1] + 3 * seq[i - 3] + P - seq[i - 2]) % P);
+        }
+        assert_eq!(berlekamp_massey(&seq, P), vec![2, P - 1, 3]);
+        assert_eq!(guess_nth_term(&seq[..6], 11, P), seq[11]);
+        assert_eq!(guess_nth_term(&seq, n, P), linear_recurrence(&[2, P - 1, 3], &[1, 4, 9], n, P));
+    }
+
+    #[test]
+    fn test_combinatorial_objects() {
+        assert_eq!(partition_count(5), 7);
+        assert_eq!(partition_unrank(4, 3), vec![3, 1]);
+        assert_eq!(partition_rank(&[3, 1]), 3);
+        assert_eq!(composition_count(4), 8);
+        assert_eq!(composition_unrank(3, 3), vec![3]);
+        assert_eq!(k_subset_unrank(4, 2, 3), vec![1, 2]);
+        assert_eq!(k_subset_rank(&[1, 2], 4), 3);
+        assert_eq!(bell_number(4), 15);
+        assert_eq!(set_partition_unrank(3, 1), vec![0, 0, 1]);
+        assert_eq!(dyck_path_unrank(2, 1), "()()");
+        assert_eq!(dyck_path_rank("(())()"), 2);
+        assert_eq!(derangement_count(4), 9);
+        assert_eq!(derangement_unrank(3, 0), vec![1, 2, 0]);
+
+        let mut rng = rand::rng();
+        for rank in 0..partition_count(8) {
+            assert_eq!(partition_rank(&partition_unrank(8, rank)), rank);
+        }
+        for rank in 0..bell_number(6) {
+            assert_eq!(set_partition_rank(&set_partition_unrank(6, rank)), rank);
+        }
+        for rank in 0..derangement_count(6) {
+            assert_eq!(derangement_rank(&derangement_unrank(6, rank)), rank);
+        }
+        let parts = random_composition_k(&mut rng, 100, 7);
+        assert_eq!((parts.len(), parts.iter().sum::<usize>()), (7, 100));
+        let path = random_dyck_path(&mut rng, 15);
+        assert_eq!(dyck_path_unrank(15, dyck_path_rank(&path)), path);
+        let p = random_derangement(&mut rng, 20);
+        assert!(p.iter().enumerate().all(|(i, &x)| i != x));
+        assert_eq!(random_k_subset(&mut rng, 10, 10), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_primality_and_factorization_64_bit() {
+        assert!(miller_rabin(3_215_031_751, 0) == is_prime(3_215_031_751));
+        assert!(!is_prime(3_215_031_751));
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(18_446_744_073_709_551_555));
+
+        let p = 1_000_000_007u64;
+        let q = 998_244_353u64;
+        assert_eq!(factor(p * q), vec![(q, 1), (p, 1)]);
+        assert_eq!(factor(2u64.pow(10) * 3 * p), vec![(2, 10), (3, 1), (p, 1)]);
+        assert_eq!(phi(p * q), (p - 1) * (q - 1));
+        assert_eq!(miu(p * q), 1);
+        assert_eq!(divisor_sum(p * q), 1 + p + q + p * q);
+        // Highly composite: sigma(n) is more than six times u64::MAX.
+        assert_eq!(divisor_sum_u128(18_401_055_938_125_660_800), 121_252_093_161_357_312_000);
+
+        // Strong pseudoprime to bases 2, 3, 5 and 7, caught by the full test.
+        assert!(miller_rabin(3_215_031_751, 4));
+        assert!(!miller_rabin(3_215_031_751, 5));
+        assert!(!miller_rabin(3_215_031_751, 0));
+
+        assert!(is_prime_u128((1u128 << 89) - 1));
+        assert!(is_prime_u128((1u128 << 127) - 1));
+        assert!(is_prime_u128(u128::MAX - 158));
+        assert!(!is_prime_u128(p as u128 * q as u128 * 3_037_000_493));
+        assert!(!is_prime_u128(((1u128 << 61) - 1) * ((1u128 << 61) - 1)));
+    }
 }
 
 pub fn is_palindromic<T: ToString>(v: T) -> bool {
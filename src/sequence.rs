@@ -137,6 +137,27 @@ pub fn geometric_sequence(
     Sequence::new(move |i, _| start * ratio.pow(i as u32))
 }
 
+/// Partition numbers p(n) from `math::partition_count`.
+/// Example: partition_numbers() -> 1, 1, 2, 3, 5, 7, ...
+#[cfg(feature = "math")]
+pub fn partition_numbers() -> Sequence<u128, impl Fn(usize, &dyn Fn(usize) -> u128) -> u128> {
+    Sequence::new(|i, _| crate::math::partition_count(i))
+}
+
+/// Bell numbers from `math::bell_number`.
+/// Example: bell_numbers() -> 1, 1, 2, 5, 15, 52, ...
+#[cfg(feature = "math")]
+pub fn bell_numbers() -> Sequence<u128, impl Fn(usize, &dyn Fn(usize) -> u128) -> u128> {
+    Sequence::new(|i, _| crate::math::bell_number(i))
+}
+
+/// Derangement numbers !n from `math::derangement_count`.
+/// Example: derangement_numbers() -> 1, 0, 1, 2, 9, 44, ...
+#[cfg(feature = "math")]
+pub fn derangement_numbers() -> Sequence<u128, impl Fn(usize, &dyn Fn(usize) -> u128) -> u128> {
+    Sequence::new(|i, _| crate::math::derangement_count(i))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,4 +182,12 @@ mod tests {
         let geo = geometric_sequence(1, 3);
         assert_eq!(geo.get_range(0, 4), vec![1, 3, 9, 27, 81]);
     }
+
+    #[cfg(feature = "math")]
+    #[test]
+    fn test_combinatorial_sequences() {
+        assert_eq!(partition_numbers().get_range(0, 5), vec![1, 1, 2, 3, 5, 7]);
+        assert_eq!(bell_numbers().get_range(0, 5), vec![1, 1, 2, 5, 15, 52]);
+        assert_eq!(derangement_numbers().get_range(0, 5), vec![1, 0, 1, 2, 9, 44]);
+    }
 }
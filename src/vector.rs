@@ -227,6 +227,52 @@ impl Vector {
     }
 }
 
+/// Random combinatorial objects from `math`, one per row.
+#[cfg(feature = "math")]
+impl Vector {
+    /// `num` uniformly random partitions of `n`, parts in non-increasing order.
+    pub fn random_partitions(num: usize, n: usize) -> IntVector {
+        let mut rng = rand::rng();
+        (0..num)
+            .map(|_| to_int_vector(crate::math::random_partition(&mut rng, n)))
+            .collect()
+    }
+
+    /// `num` uniformly random compositions of `n`, into exactly `parts`
+    /// positive parts when given.
+    pub fn random_compositions(num: usize, n: usize, parts: Option<usize>) -> IntVector {
+        let mut rng = rand::rng();
+        (0..num)
+            .map(|_| match parts {
+                Some(k) => crate::math::random_composition_k(&mut rng, n, k),
+                None => crate::math::random_composition(&mut rng, n),
+            })
+            .map(to_int_vector)
+            .collect()
+    }
+
+    /// `num` uniformly random sorted `k`-subsets of `0..n`.
+    pub fn random_subsets(num: usize, n: usize, k: usize) -> IntVector {
+        let mut rng = rand::rng();
+        (0..num)
+            .map(|_| to_int_vector(crate::math::random_k_subset(&mut rng, n, k)))
+            .collect()
+    }
+
+    /// `num` uniformly random derangements of `0..n`.
+    pub fn random_derangements(num: usize, n: usize) -> IntVector {
+        let mut rng = rand::rng();
+        (0..num)
+            .map(|_| to_int_vector(crate::math::random_derangement(&mut rng, n)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "math")]
+fn to_int_vector(values: Vec<usize>) -> Vec<i64> {
+    values.into_iter().map(|v| v as i64).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -267,4 +313,22 @@ mod tests {
         let m = vec![vec![1, 2], vec![3, 4]];
         assert_eq!(Vector::format_matrix(&m, " ", "\n"), "1 2\n3 4");
     }
+
+    #[cfg(feature = "math")]
+    #[test]
+    fn test_combinatorial_vectors() {
+        for p in Vector::random_partitions(5, 10) {
+            assert_eq!(p.iter().sum::<i64>(), 10);
+            assert!(p.windows(2).all(|w| w[0] >= w[1]));
+        }
+        for c in Vector::random_compositions(5, 10, Some(3)) {
+            assert_eq!((c.len(), c.iter().sum::<i64>()), (3, 10));
+        }
+        for s in Vector::random_subsets(5, 10, 4) {
+            assert!(s.len() == 4 && s.windows(2).all(|w| w[0] < w[1]));
+        }
+        for d in Vector::random_derangements(5, 6) {
+            assert!(d.iter().enumerate().all(|(i, &x)| x != i as i64));
+        }
+    }
 }